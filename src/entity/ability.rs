@@ -0,0 +1,111 @@
+use bracket_lib::{color::{GOLD, RED, WHITE}, prelude::{DistanceAlg::Pythagoras, Point}, random::RandomNumberGenerator};
+
+use crate::{location::{xy_idx, TileType, WorldLocation}, player::{ItemType, Material, Player, WeaponType}};
+use super::{awareness::Awareness, Entity, EntityType};
+
+const INFECTION_TURNS: i32 = 24;
+const REFORM_TURNS: u8 = 8;
+
+impl Entity {
+    pub fn is_bones(&self) -> bool {
+        self.reform > 0
+    }
+
+    pub fn cue(&self, symbol: char) -> Option<(char, (u8, u8, u8))> {
+        if self.reform > 0 {
+            Some(('%', WHITE))
+        } else if self.berserk {
+            Some(('o', RED))
+        } else if self.loot > 0 || self.pilfered > 0 {
+            Some((symbol, GOLD))
+        } else {
+            None
+        }
+    }
+
+    pub fn strike(&mut self, player: &mut Player) -> Option<String> {
+        let mut rng = RandomNumberGenerator::new();
+        match self.entity_type {
+            EntityType::Zombie if rng.range(1, 5) == 1 => {
+                player.infection = player.infection.max(INFECTION_TURNS);
+                Some(String::from("The bite festers, you feel feverish"))
+            }
+            EntityType::Goblin if player.coins > 0 && rng.range(1, 4) == 1 => {
+                let stolen = player.coins.min(rng.range(1, 6));
+                player.coins -= stolen;
+                player.stolen_coins += stolen;
+                self.loot += stolen;
+                self.fleeing = true;
+                Some(format!("{} snatches {} coins from you and runs!", self.name(), stolen))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn rage(&mut self) -> Option<String> {
+        if self.entity_type != EntityType::Orc || self.berserk || self.health*2 > self.max_health {
+            return None
+        }
+
+        self.berserk = true;
+        self.damage += self.damage/2 + 1;
+        self.hits_max += 1;
+        Some(format!("{} is wounded and goes berserk!", self.name()))
+    }
+
+    pub fn throw(&mut self, player: &mut Player, map: &mut WorldLocation) -> Option<String> {
+        if self.entity_type != EntityType::Cyclops {
+            return None
+        }
+
+        if let Some(idx) = self.charge.pop() {
+            self.charge.clear();
+            if idx == xy_idx(player.pos.0, player.pos.1, map.width) {
+                let damage = self.damage*2;
+                player.health -= damage;
+                return Some(format!("The rock smashes into you, for {}HP", damage))
+            }
+            if matches!(map.source[idx], TileType::Floor | TileType::BloodStain | TileType::Ash) && map.occupants[idx].is_none() {
+                map.source[idx] = TileType::Rubble;
+            }
+            return Some(String::from("The rock crashes into the ground and shatters"))
+        }
+
+        let distance = Pythagoras.distance2d(Point::new(self.x, self.y), Point::new(player.pos.0, player.pos.1));
+        if !player.sees((self.x, self.y)) || !(3.0..=7.0).contains(&distance) || RandomNumberGenerator::new().range(1, 5) != 1 {
+            return None
+        }
+        self.charge = vec![xy_idx(player.pos.0, player.pos.1, map.width)];
+        Some(format!("{} lifts a rock to throw at you!", self.name()))
+    }
+
+    pub fn collapse(&mut self, weapon: &Option<ItemType>) -> bool {
+        let shatters = matches!(weapon, None | Some(ItemType::Weapon(WeaponType::Mace, _, _)) |
+            Some(ItemType::Weapon(_, Material::Silver, _)));
+        if self.entity_type != EntityType::Skeleton || self.lives == 0 || shatters {
+            return false
+        }
+
+        self.lives -= 1;
+        self.reform = REFORM_TURNS;
+        true
+    }
+
+    pub fn reassemble(&mut self, player: &Player, map: &WorldLocation) -> Option<String> {
+        if self.reform == 0 {
+            return None
+        }
+
+        self.reform -= 1;
+        if self.reform > 0 {
+            return None
+        }
+        if map.occupant((self.x, self.y)).is_some() || player.pos == (self.x, self.y) {
+            self.reform = 1;
+            return None
+        }
+        self.health = self.max_health/2;
+        self.awareness = Awareness::Alerted;
+        player.sees((self.x, self.y)).then(|| format!("The bones of {} knit back together!", self.name()))
+    }
+}
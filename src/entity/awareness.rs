@@ -0,0 +1,141 @@
+use std::fmt;
+
+use bracket_lib::{prelude::{DistanceAlg::Pythagoras, Point}, random::RandomNumberGenerator};
+
+use crate::player::Player;
+use super::{Entity, EntityType};
+
+const SEARCH_TURNS: u8 = 30;
+const HEARING_RANGE: f32 = 10.0;
+const NOISE_BONUS: i32 = 30;
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum Awareness {
+    Sleeping,
+    #[default]
+    Idle,
+    Wandering,
+    Alerted,
+    Searching,
+}
+
+impl fmt::Display for Awareness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Awareness::Sleeping => write!(f, "asleep"),
+            Awareness::Idle => write!(f, "idle"),
+            Awareness::Wandering => write!(f, "wandering"),
+            Awareness::Alerted => write!(f, "hunting you"),
+            Awareness::Searching => write!(f, "searching for you"),
+        }
+    }
+}
+
+impl Entity {
+    pub fn first_awareness(entity_type: EntityType) -> Awareness {
+        match entity_type {
+            EntityType::OrcWarlord | EntityType::ElderCyclops => Awareness::Idle,
+            EntityType::Human | EntityType::Trader => Awareness::Wandering,
+            _ => match RandomNumberGenerator::new().range(0, 3) {
+                0 => Awareness::Sleeping,
+                1 => Awareness::Idle,
+                _ => Awareness::Wandering,
+            },
+        }
+    }
+
+    fn find_phrase(&self) -> Option<&'static str> {
+        match self.entity_type {
+            EntityType::Zombie => Some("groans and shambles towards you"),
+            EntityType::Skeleton => Some("rattles its bones at you"),
+            EntityType::Orc => Some("yells at you!"),
+            EntityType::Cyclops => Some("roars and beats its chest"),
+            EntityType::Goblin => Some("shrieks and points at you"),
+            EntityType::Bandit => Some("grins and eyes your purse"),
+            EntityType::OrcWarlord => Some("bellows a war cry!"),
+            EntityType::ElderCyclops => Some("fixes its huge eye on you"),
+            EntityType::Human | EntityType::Trader => None,
+        }
+    }
+
+    fn search_phrase(&self) -> Option<&'static str> {
+        match self.entity_type {
+            EntityType::Zombie => Some("You hear a hungry moan somewhere nearby"),
+            EntityType::Skeleton => Some("You hear bones clattering nearby"),
+            EntityType::Orc => Some("You hear an orc cursing, it has lost sight of you"),
+            EntityType::Cyclops => Some("You hear heavy footsteps searching for you"),
+            EntityType::Goblin => Some("You hear a goblin sniffing the air"),
+            EntityType::Bandit => Some("You hear soft footsteps creeping around"),
+            EntityType::OrcWarlord => Some("You hear the warlord barking orders"),
+            EntityType::ElderCyclops => Some("The ground trembles as something searches for you"),
+            EntityType::Human | EntityType::Trader => None,
+        }
+    }
+
+    fn wakes_up(&self, player: &Player) -> bool {
+        let distance = Pythagoras.distance2d(Point::new(player.pos.0, player.pos.1), Point::new(self.x, self.y));
+        let mut chance = 10 + (player.weight / player.max_weight * 40.0) as i32 - player.agility*5;
+        if distance <= 2.0 {
+            chance += 20;
+        }
+        if self.alert > 0 {
+            chance += 40;
+        }
+        if self.noise {
+            chance += NOISE_BONUS;
+        }
+        RandomNumberGenerator::new().range(1, 101) <= chance.clamp(2, 95)
+    }
+
+    pub fn perceive(&mut self, player: &Player) -> Option<String> {
+        let sees = player.sees((self.x, self.y)) || self.alert > 0;
+        let distance = Pythagoras.distance2d(Point::new(player.pos.0, player.pos.1), Point::new(self.x, self.y));
+        let state = match self.awareness {
+            Awareness::Sleeping => {
+                let woken = (sees || self.noise) && self.wakes_up(player);
+                self.noise = false;
+                if !woken {
+                    Awareness::Sleeping
+                } else if sees {
+                    Awareness::Alerted
+                } else {
+                    self.search = SEARCH_TURNS;
+                    Awareness::Searching
+                }
+            }
+            _ if sees => Awareness::Alerted,
+            Awareness::Alerted => {
+                self.search = SEARCH_TURNS;
+                Awareness::Searching
+            }
+            Awareness::Searching => {
+                self.search = self.search.saturating_sub(1);
+                if self.search == 0 { Awareness::Wandering } else { Awareness::Searching }
+            }
+            state => state,
+        };
+
+        if state == self.awareness {
+            return None
+        }
+        let was = self.awareness;
+        self.awareness = state;
+        match state {
+            Awareness::Alerted if was == Awareness::Sleeping => Some(format!("{} wakes up!", self.name())),
+            Awareness::Alerted if was == Awareness::Searching => None,
+            Awareness::Alerted => self.find_phrase().map(|phrase| format!("{} {}", self.name(), phrase)),
+            Awareness::Searching if distance <= HEARING_RANGE => self.search_phrase().map(String::from),
+            Awareness::Wandering if player.sees((self.x, self.y)) => Some(format!("{} gives up the search", self.name())),
+            _ => None,
+        }
+    }
+
+    pub fn startle(&mut self) {
+        if self.awareness == Awareness::Sleeping {
+            self.noise = true;
+        } else if self.awareness != Awareness::Alerted {
+            self.awareness = Awareness::Searching;
+            self.search = SEARCH_TURNS;
+        }
+    }
+}
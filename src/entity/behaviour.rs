@@ -0,0 +1,80 @@
+use bracket_lib::prelude::{BaseMap, DistanceAlg::Pythagoras, Point};
+
+use crate::{location::{xy_idx, WorldLocation}, player::Player};
+use super::{awareness::Awareness, Entity};
+
+const RALLY_RADIUS: i32 = 6;
+const FLANK_RANGE: f32 = 6.0;
+const GUARD_RADIUS: f32 = 8.0;
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum Behaviour {
+    #[default]
+    Peaceful,
+    Shamble,
+    Guard,
+    Flank,
+    Coward,
+    Thief,
+    Brute,
+}
+
+fn distance((x1, y1): (i32, i32), (x2, y2): (i32, i32)) -> f32 {
+    Pythagoras.distance2d(Point::new(x1, y1), Point::new(x2, y2))
+}
+
+impl Entity {
+    fn allies_near(&self, (x, y): (i32, i32), radius: f32, map: &WorldLocation) -> Vec<(i32, i32)> {
+        map.entities.iter()
+            .filter(|e| e.health > 0 && !e.is_friendly() && e.awareness != Awareness::Sleeping)
+            .map(|e| (e.x, e.y))
+            .filter(|pos| *pos != (self.x, self.y) && distance(*pos, (x, y)) <= radius)
+            .collect()
+    }
+
+    pub fn leashed(&self, player: &Player) -> bool {
+        self.behaviour == Behaviour::Guard && distance(player.pos, self.lair) > GUARD_RADIUS
+    }
+
+    pub fn muster(&mut self, map: &mut WorldLocation) -> Option<String> {
+        if self.behaviour != Behaviour::Coward {
+            return None
+        }
+
+        let friends = self.allies_near((self.x, self.y), RALLY_RADIUS as f32, map).len();
+        if !self.fleeing && self.health*3 <= self.max_health && friends < 2 {
+            self.fleeing = true;
+            map.make_noise((self.x, self.y), RALLY_RADIUS*2);
+            Some(format!("{} flees, shrieking for help!", self.name()))
+        } else if self.fleeing && friends >= 2 {
+            self.fleeing = false;
+            Some(format!("{} comes back with its friends!", self.name()))
+        } else {
+            None
+        }
+    }
+
+    pub fn flank(&self, player: &Player, map: &WorldLocation) -> Option<usize> {
+        let (px, py) = player.pos;
+        let allies = self.allies_near(player.pos, FLANK_RANGE, map);
+        let reach = distance((self.x, self.y), player.pos);
+        if self.behaviour != Behaviour::Flank || allies.is_empty() || reach > FLANK_RANGE || reach <= 1.0 {
+            return None
+        }
+
+        let slot = [(px+1, py), (px-1, py), (px, py+1), (px, py-1)].into_iter()
+            .filter(|pos| !map.is_solid(*pos))
+            .max_by(|a, b| {
+                let score = |pos: (i32, i32)| allies.iter().map(|ally| distance(*ally, pos)).fold(f32::MAX, f32::min)*2.0 -
+                    distance((self.x, self.y), pos);
+                score(*a).total_cmp(&score(*b))
+            })?;
+        let here = distance((self.x, self.y), slot);
+        map.get_available_exits(xy_idx(self.x, self.y, map.width)).iter()
+            .filter(|(next, _)| map.tile_cost(*next) <= 1.0)
+            .map(|(next, _)| (*next as i32 % map.width, *next as i32 / map.width))
+            .filter(|pos| *pos != player.pos && distance(*pos, slot) < here)
+            .min_by(|a, b| distance(*a, slot).total_cmp(&distance(*b, slot)))
+            .map(|(x, y)| xy_idx(x, y, map.width))
+    }
+}
@@ -0,0 +1,93 @@
+use bracket_lib::{prelude::{DistanceAlg::Pythagoras, Point}, random::RandomNumberGenerator};
+
+use crate::{location::{xy_idx, WorldLocation}, player::Player};
+use super::{Entity, EntityType};
+
+impl Entity {
+    pub fn is_boss(&self) -> bool {
+        matches!(self.entity_type, EntityType::OrcWarlord | EntityType::ElderCyclops)
+    }
+
+    fn phase_of(&self) -> u8 {
+        if self.health * 3 <= self.max_health {
+            3
+        } else if self.health * 3 <= self.max_health * 2 {
+            2
+        } else {
+            1
+        }
+    }
+
+    pub fn boss_turn(&mut self, player: &mut Player, map: &mut WorldLocation) -> Option<String> {
+        let name = self.name();
+        let phase = self.phase_of();
+        if phase > self.phase {
+            let mut events = vec![];
+            while self.phase < phase {
+                self.phase += 1;
+                events.push(match self.phase {
+                    2 => self.summon(player, map),
+                    _ => {
+                        self.damage += self.damage/2;
+                        format!("{} flies into a rage!", name)
+                    }
+                });
+            }
+            return Some(events.join("\n"))
+        }
+
+        if !self.charge.is_empty() {
+            let hit = self.charge.contains(&xy_idx(player.pos.0, player.pos.1, map.width));
+            self.charge.clear();
+            return Some(if hit {
+                let damage = self.damage*3;
+                player.health -= damage;
+                match self.entity_type {
+                    EntityType::OrcWarlord => format!("{} cleaves through you, for {}HP", name, damage),
+                    _ => format!("The boulder crushes you, for {}HP", damage),
+                }
+            } else {
+                match self.entity_type {
+                    EntityType::OrcWarlord => format!("{} swings at empty air", name),
+                    _ => String::from("The boulder smashes into the ground next to you"),
+                }
+            })
+        }
+
+        let distance = Pythagoras.distance2d(Point::new(self.x, self.y), Point::new(player.pos.0, player.pos.1));
+        if distance > 5.0 || RandomNumberGenerator::new().range(1, 5) != 1 {
+            return None
+        }
+
+        let (cx, cy, message) = match self.entity_type {
+            EntityType::OrcWarlord => (self.x, self.y, format!("{} raises his axe for a wide swing!", name)),
+            _ => (player.pos.0, player.pos.1, format!("{} lifts a boulder over its head!", name)),
+        };
+        for y in cy-1..=cy+1 {
+            for x in cx-1..=cx+1 {
+                if x >= 0 && y >= 0 && x < map.width && y < map.height && (x, y) != (self.x, self.y) {
+                    self.charge.push(xy_idx(x, y, map.width));
+                }
+            }
+        }
+        Some(message)
+    }
+
+    fn summon(&mut self, player: &Player, map: &mut WorldLocation) -> String {
+        let (kind, message) = match self.entity_type {
+            EntityType::OrcWarlord => (EntityType::Orc, format!("{} bellows and his guards rush in!", self.name())),
+            _ => (EntityType::Goblin, format!("{} roars and goblins pour out of the cracks!", self.name())),
+        };
+
+        let mut count = 0;
+        for (x, y) in [(self.x+1, self.y), (self.x-1, self.y), (self.x, self.y+1), (self.x, self.y-1)] {
+            if count < 2 && !map.is_solid((x, y)) && (x, y) != player.pos {
+                let mut minion = Entity::new(x, y, kind, player.exp);
+                minion.alert = 10;
+                map.summon(minion);
+                count += 1;
+            }
+        }
+        message
+    }
+}
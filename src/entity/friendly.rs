@@ -0,0 +1,43 @@
+use bracket_lib::random::RandomNumberGenerator;
+
+use crate::{location::WorldLocation, player::Player};
+use super::{Entity, EntityType};
+
+const GREETINGS: [&str; 4] = [
+    "The traveler greets you!",
+    "The traveler nods at you and walks on",
+    "The traveler warns you that the dungeon has swallowed many before you",
+    "The traveler wishes you luck below",
+];
+
+impl Entity {
+    pub fn is_friendly(&self) -> bool {
+        matches!(self.entity_type, EntityType::Human | EntityType::Trader)
+    }
+
+    pub fn is_trader(&self) -> bool {
+        self.entity_type == EntityType::Trader
+    }
+
+    pub fn greet(&self) -> String {
+        String::from(GREETINGS[RandomNumberGenerator::new().range(0, GREETINGS.len())])
+    }
+
+    pub fn wander(&mut self, player: &Player, map: &WorldLocation) {
+        if self.is_trader() {
+            return
+        }
+
+        let (x, y) = match RandomNumberGenerator::new().range(0, 8) {
+            0 => (self.x+1, self.y),
+            1 => (self.x-1, self.y),
+            2 => (self.x, self.y+1),
+            3 => (self.x, self.y-1),
+            _ => return,
+        };
+        if !map.is_solid((x, y)) && (x, y) != player.pos {
+            self.x = x;
+            self.y = y;
+        }
+    }
+}
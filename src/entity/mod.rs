@@ -1,25 +1,59 @@
 use std::fmt;
 
-use crate::{location::{xy_idx, WorldLocation}, player::Player, State};
+pub mod ability;
+pub mod awareness;
+pub mod behaviour;
+pub mod boss;
+pub mod friendly;
+pub mod roam;
+pub mod thief;
+
+use crate::{location::{flow::Goal, item::LURE_RADIUS, xy_idx, TileType, WorldLocation}, player::{ItemType, Player}, State};
+use self::{awareness::Awareness, behaviour::Behaviour};
 use bracket_lib::{
-    color::WHITE, pathfinding::a_star_search, prelude::{BTerm, DistanceAlg::Pythagoras, Point}, random::RandomNumberGenerator, terminal::GREEN,
+    color::{GOLD, RED, WHITE}, prelude::{BTerm, DistanceAlg::Pythagoras, Point}, random::RandomNumberGenerator, terminal::GREEN,
 };
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
 pub enum EntityType {
     Zombie,
     Skeleton,
     Orc,
     Cyclops,
     Goblin,
+    Bandit,
+    OrcWarlord,
+    ElderCyclops,
+    #[default]
+    Human,
+    Trader,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Entity {
     pub x: i32,
     pub y: i32,
     pub health: i32,
     pub damage: i32,
+    pub alert: u8,
+    pub carried: Vec<ItemType>,
+    pub max_health: i32,
+    pub phase: u8,
+    pub charge: Vec<usize>,
+    pub stock: Vec<(ItemType, i32)>,
+    pub lair: (i32, i32),
+    pub target: Option<(i32, i32)>,
+    pub route: Vec<usize>,
+    pub awareness: Awareness,
+    pub behaviour: Behaviour,
+    fleeing: bool,
+    pub loot: i32,
+    pilfered: usize,
+    berserk: bool,
+    lives: u8,
+    reform: u8,
+    search: u8,
+    noise: bool,
     entity_type: EntityType,
     hits: u8,
     hits_max: u8,
@@ -27,18 +61,27 @@ pub struct Entity {
 
 impl fmt::Display for EntityType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            EntityType::OrcWarlord => write!(f, "Orc Warlord"),
+            EntityType::ElderCyclops => write!(f, "Elder Cyclops"),
+            EntityType::Human => write!(f, "Traveler"),
+            _ => write!(f, "{:?}", self),
+        }
     }
 }
 
 impl Entity {
     pub fn new(x: i32, y: i32, t: EntityType, exp: i32) -> Self {
-        let (damage, max_hits, mut health) = match t {
-            EntityType::Skeleton => (1, 3, 12),
-            EntityType::Zombie => (1, 5, 15),
-            EntityType::Orc => (3, 1, 20),
-            EntityType::Cyclops => (4, 1, 25),
-            EntityType::Goblin => (2, 2, 15),
+        let (damage, max_hits, mut health, behaviour) = match t {
+            EntityType::Skeleton => (1, 3, 12, Behaviour::Guard),
+            EntityType::Zombie => (1, 5, 15, Behaviour::Shamble),
+            EntityType::Orc => (3, 1, 20, Behaviour::Flank),
+            EntityType::Cyclops => (4, 1, 25, Behaviour::Brute),
+            EntityType::Goblin => (2, 2, 15, Behaviour::Coward),
+            EntityType::Bandit => (2, 1, 14, Behaviour::Thief),
+            EntityType::OrcWarlord => (4, 1, 60, Behaviour::Flank),
+            EntityType::ElderCyclops => (6, 1, 80, Behaviour::Brute),
+            EntityType::Human | EntityType::Trader => (0, 0, 10, Behaviour::Peaceful),
         };
 
         health *= 1+exp/100;
@@ -48,21 +91,54 @@ impl Entity {
             entity_type: t,
             damage: damage,
             hits: 0,
+            alert: 0,
+            carried: vec![],
+            max_health: health,
+            phase: 1,
+            charge: vec![],
+            stock: vec![],
+            lair: (x, y),
+            target: None,
+            route: vec![],
+            awareness: Entity::first_awareness(t),
+            search: 0,
+            noise: false,
+            behaviour,
+            fleeing: false,
+            loot: 0,
+            pilfered: 0,
+            berserk: false,
+            lives: if t == EntityType::Skeleton { 2 } else { 0 },
+            reform: 0,
             hits_max: max_hits,
             health: health,
         }
     }
 
-    fn draw(&self, engine: &mut BTerm, player_pos: (i32, i32)) {
+    pub fn can_smash(&self) -> bool {
+        self.behaviour == Behaviour::Brute
+    }
+
+    pub fn is_fleeing(&self) -> bool {
+        self.fleeing
+    }
+
+    pub fn draw(&self, engine: &mut BTerm, player_pos: (i32, i32)) {
         let data = match self.entity_type {
             EntityType::Zombie => ('Z', GREEN),
             EntityType::Skeleton => ('s', WHITE),
             EntityType::Orc => ('o', GREEN),
             EntityType::Cyclops => ('c', GREEN),
             EntityType::Goblin => ('g', GREEN),
+            EntityType::Bandit => ('b', GREEN),
+            EntityType::OrcWarlord => ('Ö', RED),
+            EntityType::ElderCyclops => ('C', RED),
+            EntityType::Human => ('☺', WHITE),
+            EntityType::Trader => ('☺', GOLD),
         };
+        let data = self.cue(data.0).unwrap_or(data);
         
-        if self.health > 0 {
+        if self.health > 0 || self.is_bones() {
             State::put(engine, player_pos, self.x, self.y, data.1, data.0);
         } else {
             State::put_red(engine, player_pos, self.x, self.y, data.1, data.0);
@@ -74,6 +150,14 @@ impl Entity {
     }
     
     pub fn update(&mut self, player: &mut Player, map: &mut WorldLocation, engine: &mut BTerm) -> String {
+        if self.is_friendly() {
+            self.wander(player, map);
+            if player.sees((self.x, self.y)) {
+                self.draw(engine, player.pos);
+            }
+            return String::new()
+        }
+
         let luck;
         if player.exp >= 100 {
             luck = RandomNumberGenerator::new().range(1, 4); 
@@ -83,66 +167,143 @@ impl Entity {
         let damage = self.damage * luck*(1+player.exp/50);
 
         let mut ret = String::from("");
+        self.alert = self.alert.saturating_sub(1);
         if self.health > 0 {
             let s: String = self.name();
-            let path = a_star_search(
-                xy_idx(self.x, self.y, map.width),
-                xy_idx(player.pos.0, player.pos.1, map.width),
-                &*map,
-            );
-            if path.steps.len() > 1 {
-                let future_x = path.steps[1] as i32 % map.width;
-                let future_y = path.steps[1] as i32 / map.width;
-
-                if !map.is_solid((future_x, future_y)) && (future_x, future_y) != player.pos 
-                    && self.hits < self.hits_max {
-                    self.x = future_x;
-                    self.y = future_y;
+            let start = (self.x, self.y);
+            let was = self.awareness;
+            let bark = self.perceive(player);
+            if was == Awareness::Sleeping {
+                if player.sees((self.x, self.y)) {
+                    self.draw(engine, player.pos);
+                }
+                return bark.unwrap_or_default()
+            }
+            if let Some(bark) = bark {
+                ret = bark;
+            }
+
+            if self.is_boss() && self.awareness == Awareness::Alerted {
+                if let Some(event) = self.boss_turn(player, map) {
+                    if player.sees((self.x, self.y)) {
+                        self.draw(engine, player.pos);
+                    }
+                    return event
+                }
+            }
+
+            if self.awareness != Awareness::Alerted || self.leashed(player) {
+                if self.leashed(player) && self.route.is_empty() {
+                    self.target = Some(self.lair);
                 }
+                self.roam(player, map);
+            } else {
+                if let Some(event) = self.throw(player, map) {
+                    if player.sees((self.x, self.y)) {
+                        self.draw(engine, player.pos);
+                    }
+                    return event
+                }
+                if let Some(event) = self.muster(map).or_else(|| self.rage()) {
+                    ret = event;
+                }
+                self.target = Some(player.pos);
+                self.route.clear();
+                map.smash = self.can_smash();
+                let idx = xy_idx(self.x, self.y, map.width);
+                let lure = map.flow_at(Goal::Items, idx);
+                let goal = if self.is_fleeing() {
+                    Goal::Flee
+                } else if self.behaviour != Behaviour::Shamble && lure <= LURE_RADIUS as f32 && lure < map.flow_at(Goal::Pursuit, idx) {
+                    Goal::Items
+                } else if map.smash {
+                    Goal::Smash
+                } else {
+                    Goal::Pursuit
+                };
+                let mut step = self.flank(player, map).or_else(|| map.step_down(goal, idx));
+                if step.is_none() && self.hits < self.hits_max {
+                    if let Some(next) = map.blocker(goal, idx) {
+                        let j = map.occupants[next].unwrap();
+                        if map.entities[j].is_fleeing() != self.is_fleeing() {
+                            map.swap(j, (self.x, self.y));
+                            step = Some(next);
+                        }
+                    }
+                }
+                if let Some(next) = step {
+                    let future_x = next as i32 % map.width;
+                    let future_y = next as i32 / map.width;
+
+                    if map.is_breakable(next) {
+                        if map.damage_wall(next, self.damage*3) {
+                            ret = format!("{} smashes through the wall!", s.clone());
+                        }
+                    } else if !map.is_solid((future_x, future_y)) && (future_x, future_y) != player.pos 
+                        && self.hits < self.hits_max {
+                        self.x = future_x;
+                        self.y = future_y;
+                    }
+
+                    if self.hits >= self.hits_max {
+                        self.hits = 0;
+                    } 
+                }
+                map.smash = false;
+            }
 
-                if self.hits >= self.hits_max {
-                    self.hits = 0;
-                } 
+            if map.source[xy_idx(self.x, self.y, map.width)] == TileType::Lava {
+                self.health -= 5;
+                ret = format!("{} is burned by lava", s.clone());
+            }
+
+            if map.source[xy_idx(self.x, self.y, map.width)] == TileType::Item {
+                let mut items = map.take_items(xy_idx(self.x, self.y, map.width));
+                if player.sees((self.x, self.y)) {
+                    let names: Vec<String> = items.iter().map(Player::item_name).collect();
+                    ret = format!("{} snatches up {} from the floor", s.clone(), names.join(", "));
+                }
+                self.carried.append(&mut items);
+            }
+
+            if (self.x, self.y) != start && map.trap_at(xy_idx(self.x, self.y, map.width)).is_some() {
+                let event = map.trigger_trap(self, player.pos);
+                if player.sees((self.x, self.y)) {
+                    ret = event;
+                }
             }
 
             if Pythagoras.distance2d(
                 Point::new(player.pos.0, player.pos.1),
                 Point::new(self.x, self.y),
             ) <= 1.0 {  
-                // if player.coins > 0 && self.entity_type == EntityType::Bandit {
-                //     if player.coins < damage as i32 {
-                //         let msg;
-                //         if player.coins == 1 {
-                //             msg = "coin";
-                //         } else {
-                //             msg = "coins";
-                //         }
-
-                //         let str = format!("The {} has stoled from you {} {}!", s.clone(), player.coins,
-                //             msg);
-                //         player.coins = 0;
-                //         ret = str;
-                //     } else {
-                //         player.coins -= damage as i32;
-                //         ret = format!("The {} has stoled from you {} coins!", s.clone(), damage);
-                //     }
-                // } else {
-                self.hits += 1;
-
-                if RandomNumberGenerator::new().range(1, 101) >= player.luck*player.agility*4-5 {
-                    if RandomNumberGenerator::new().range(0, 11) <= 2 {
-                        player.health -= damage*2;
-                        ret = format!("{} critically hits player, for {}HP", s.clone(), damage*2);
-                    } else {
-                        player.health -= damage;
-                        ret = format!("{} hits player, for {}HP", s.clone(), damage);
+                if let Some(event) = self.pilfer(player) {
+                    ret = event;
+                } else {
+                    self.hits += 1;
+
+                    if RandomNumberGenerator::new().range(1, 101) >= player.luck*player.agility*4-5 {
+                        if RandomNumberGenerator::new().range(0, 11) <= 2 {
+                            player.health -= damage*2;
+                            ret = format!("{} critically hits player, for {}HP", s.clone(), damage*2);
+                        } else {
+                            player.health -= damage;
+                            ret = format!("{} hits player, for {}HP", s.clone(), damage);
+                        }
+                        if let Some(event) = self.strike(player) {
+                            ret = format!("{}\n{}", ret, event);
+                        }
+                    } else if player.weight <= player.max_weight/2.0 {
+                        ret = format!("Player evades from {} hit", s.clone());
                     }
-                } else if player.weight <= player.max_weight/2.0 {
-                    ret = format!("Player evades from {} hit", s.clone());
                 }
             }
+        } else if let Some(event) = self.reassemble(player, map) {
+            ret = event;
+        }
+        if player.sees((self.x, self.y)) {
+            self.draw(engine, player.pos);
         }
-        self.draw(engine, player.pos);
         ret
     }
 }
@@ -0,0 +1,76 @@
+use bracket_lib::{pathfinding::a_star_search, prelude::{DistanceAlg::Pythagoras, Point}};
+
+use crate::{location::{xy_idx, WorldLocation}, player::Player};
+use super::{awareness::Awareness, Entity, EntityType};
+
+const NEAR_RANGE: f32 = 20.0;
+const FAR_RATE: usize = 4;
+const LAIR_RADIUS: f32 = 6.0;
+
+impl Entity {
+    pub fn should_act(&self, player: &Player, tick: usize) -> bool {
+        if self.health <= 0 && !self.is_bones() {
+            return false
+        }
+        player.sees((self.x, self.y)) || self.alert > 0 || tick.is_multiple_of(FAR_RATE) ||
+            Pythagoras.distance2d(Point::new(player.pos.0, player.pos.1), Point::new(self.x, self.y)) <= NEAR_RANGE
+    }
+
+    pub fn can_track(&self) -> bool {
+        matches!(self.entity_type, EntityType::Zombie | EntityType::Goblin)
+    }
+
+    pub fn roam(&mut self, player: &Player, map: &WorldLocation) {
+        if self.route.is_empty() && self.awareness == Awareness::Searching && self.can_track() {
+            if let Some(next) = map.sniff(xy_idx(self.x, self.y, map.width)) {
+                let (x, y) = (next as i32 % map.width, next as i32 / map.width);
+                if (x, y) != player.pos {
+                    self.target = None;
+                    self.x = x;
+                    self.y = y;
+                    return
+                }
+            }
+        }
+
+        if self.route.is_empty() {
+            let home = Pythagoras.distance2d(Point::new(self.lair.0, self.lair.1), Point::new(self.x, self.y));
+            let goal = match self.target.take() {
+                Some(target) => target,
+                None if home > LAIR_RADIUS => self.lair,
+                None => {
+                    if self.awareness != Awareness::Idle {
+                        self.wander(player, map);
+                    }
+                    return
+                }
+            };
+
+            let path = a_star_search(xy_idx(self.x, self.y, map.width), xy_idx(goal.0, goal.1, map.width), map);
+            if !path.success {
+                if goal == self.lair {
+                    self.lair = (self.x, self.y);
+                }
+                return
+            }
+            self.route = path.steps.into_iter().skip(1).rev().collect();
+        }
+
+        if let Some(next) = self.route.last() {
+            let (x, y) = (*next as i32 % map.width, *next as i32 / map.width);
+            if map.is_solid((x, y)) || (x, y) == player.pos {
+                self.route.clear();
+                return
+            }
+            self.route.pop();
+            self.x = x;
+            self.y = y;
+        }
+    }
+
+    pub fn hear(&mut self, (x, y): (i32, i32)) {
+        self.target = Some((x, y));
+        self.route.clear();
+        self.startle();
+    }
+}
@@ -0,0 +1,57 @@
+use bracket_lib::{color::GOLD, random::RandomNumberGenerator};
+
+use crate::{location::{xy_idx, TileType, WorldLocation}, player::Player};
+use super::{behaviour::Behaviour, Entity};
+
+impl Entity {
+    pub fn pilfer(&mut self, player: &mut Player) -> Option<String> {
+        if self.behaviour != Behaviour::Thief || self.loot > 0 || self.pilfered > 0 {
+            return None
+        }
+
+        let mut rng = RandomNumberGenerator::new();
+        if rng.range(1, 101) <= player.agility*8 {
+            return Some(format!("{} tries to pick your pocket, but you catch its hand", self.name()))
+        }
+        let event = if player.coins > 0 && (player.inventory.is_empty() || rng.range(1, 3) == 1) {
+            let coins = player.coins.min(rng.range(3, 9));
+            player.coins -= coins;
+            player.stolen_coins += coins;
+            self.loot += coins;
+            format!("{} grabs {} coins from your purse and runs off!", self.name(), coins)
+        } else if !player.inventory.is_empty() {
+            let item = player.inventory.remove(rng.range(0, player.inventory.len()));
+            player.weight -= Player::weight_of(&item);
+            player.stolen_items += 1;
+            self.pilfered += 1;
+            let event = format!("{} snatches your {} and runs off!", self.name(), Player::item_name(&item));
+            self.carried.push(item);
+            event
+        } else {
+            return None
+        };
+        self.fleeing = true;
+        Some(event)
+    }
+}
+
+impl WorldLocation {
+    pub fn drop_purse(&mut self, i: usize, player: &mut Player) -> Option<String> {
+        let entity = &mut self.entities[i];
+        player.stolen_items = player.stolen_items.saturating_sub(entity.pilfered);
+        entity.pilfered = 0;
+        let loot = std::mem::take(&mut entity.loot);
+        if loot == 0 {
+            return None
+        }
+
+        player.stolen_coins = (player.stolen_coins - loot).max(0);
+        let idx = xy_idx(entity.x, entity.y, self.width);
+        let spot = std::iter::once(idx).chain(self.neighbours(idx))
+            .find(|i| matches!(self.source[*i], TileType::Floor | TileType::BloodStain | TileType::Ash | TileType::Coin))?;
+        self.source[spot] = TileType::Coin;
+        self.colors[spot] = GOLD;
+        *self.purses.entry(spot).or_default() += loot;
+        Some(format!("{} coins", loot))
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use bracket_lib::{color::{BURLYWOOD, DIM_GRAY, DODGER_BLUE, GRAY, GREY100, ORANGE, ORANGE_RED, RGB, WHITE}, terminal::BTerm};
+
+use crate::entity::{Entity, EntityType};
+use super::{flow::Flow, xy_idx, TileType, WorldLocation};
+
+pub const BOSS_INTERVAL: i32 = 4;
+
+const WARLORD_HALL: [&str; 13] = [
+    "###############################",
+    "#.............................#",
+    "#..##.....##.......##.....##..#",
+    "#..##.....##.......##.....##..#",
+    "#.............................#",
+    "#.............................#",
+    "#@U...........B.............X.#",
+    "#.............................#",
+    "#.............................#",
+    "#..##.....##.......##.....##..#",
+    "#..##.....##.......##.....##..#",
+    "#.............................#",
+    "###############################",
+];
+
+const CYCLOPS_CAVE: [&str; 13] = [
+    "###############################",
+    "#######.......................#",
+    "####.......LLL.........O......#",
+    "##........LLLLL...............#",
+    "#..........LLL.........~~~....#",
+    "#@U....................~~~....#",
+    "#...........B..........~~~..X.#",
+    "#......O......................#",
+    "##..........................###",
+    "####.......LLL........O.....###",
+    "#######...LLLLL...........#####",
+    "##########.LLL.##.....#########",
+    "###############################",
+];
+
+impl WorldLocation {
+    pub fn arena(engine: &mut BTerm, width: i32, height: i32, exp: i32, floor: i32, treasure: bool) -> Self {
+        let (prefab, boss) = if (floor / BOSS_INTERVAL) % 2 == 1 {
+            (WARLORD_HALL, EntityType::OrcWarlord)
+        } else {
+            (CYCLOPS_CAVE, EntityType::ElderCyclops)
+        };
+        WorldLocation::burn_arena(engine);
+
+        let mut map = vec![TileType::Wall; (width * height) as usize];
+        let mut colors = vec![GREY100; (width * height) as usize];
+        let mut entities = vec![];
+        let (mut start, mut sealed) = ((0, 0), None);
+
+        let left = (width - prefab[0].len() as i32) / 2;
+        let top = (height - prefab.len() as i32) / 2;
+        for (y, row) in prefab.iter().enumerate() {
+            for (x, symbol) in row.chars().enumerate() {
+                let (x, y) = (left + x as i32, top + y as i32);
+                let idx = xy_idx(x, y, width);
+                let (tile, color) = match symbol {
+                    '#' => (TileType::Wall, GREY100),
+                    'L' => (TileType::Lava, ORANGE),
+                    '~' => (TileType::ShallowWater, DODGER_BLUE),
+                    'O' => (TileType::Boulder, BURLYWOOD),
+                    'U' => (TileType::UpStair, WHITE),
+                    'X' => {
+                        sealed = Some((idx, if treasure { TileType::Artifact } else { TileType::Exit }));
+                        (TileType::SealedExit, DIM_GRAY)
+                    }
+                    _ => (TileType::Floor, GRAY),
+                };
+                map[idx] = tile;
+                colors[idx] = color;
+
+                match symbol {
+                    '@' => start = (x, y),
+                    'B' => entities.push(Entity::new(x, y, boss, exp)),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut result = Self {
+            durability: map.iter().map(|tile| WorldLocation::durability_of(*tile)).collect(),
+            gas: vec![0; map.len()],
+            fire: vec![0; map.len()],
+            scent: vec![0; map.len()],
+            doorways: vec![],
+            mechanisms: vec![],
+            portals: vec![],
+            items: HashMap::new(),
+            purses: HashMap::new(),
+            occupants: vec![],
+            summons: vec![],
+            prompts: vec![],
+            sealed,
+            flow: Flow::default(),
+            source: map,
+            entities,
+            colors,
+            width,
+            height,
+            start_x: start.0,
+            start_y: start.1,
+            smash: false,
+            dirty: false,
+            alarm: false,
+            turn: 0,
+        };
+        result.index_entities();
+        result
+    }
+
+    pub fn burn_arena(engine: &mut BTerm) {
+        engine.screen_burn_color(RGB::from_u8(ORANGE_RED.0, ORANGE_RED.1, ORANGE_RED.2));
+    }
+
+    pub fn boss(&self) -> Option<&Entity> {
+        self.entities.iter().find(|e| e.is_boss() && e.health > 0)
+    }
+}
@@ -0,0 +1,154 @@
+use bracket_lib::random::RandomNumberGenerator;
+
+use super::arena::BOSS_INTERVAL;
+
+pub const FINAL_DEPTH: i32 = 8;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Theme {
+    Main,
+    Warrens,
+    Crypt,
+    Flooded,
+}
+
+impl Theme {
+    pub fn name(&self) -> &str {
+        match self {
+            Theme::Main => "dungeon",
+            Theme::Warrens => "goblin warrens",
+            Theme::Crypt => "crypt",
+            Theme::Flooded => "flooded cave",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Branch {
+    pub theme: Theme,
+    pub entry: i32,
+    pub length: i32,
+    pub cleared: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Dungeon {
+    pub branches: Vec<Branch>,
+    pub current: Option<usize>,
+    pub level: i32,
+    pub main: i32,
+    pub deepest: i32,
+    pub final_depth: Option<i32>,
+    pub escaping: bool,
+}
+
+impl Dungeon {
+    pub fn new(rng: &mut RandomNumberGenerator) -> Self {
+        let mut themes = vec![Theme::Warrens, Theme::Crypt, Theme::Flooded];
+        let mut branches = vec![];
+        for entry in [rng.range(2, 4), rng.range(5, 8)] {
+            let theme = themes.remove(rng.range(0, themes.len()));
+            branches.push(Branch {
+                theme,
+                entry,
+                length: rng.range(2, 4),
+                cleared: false,
+            });
+        }
+
+        Self {
+            branches,
+            current: None,
+            level: 0,
+            main: 0,
+            deepest: 0,
+            final_depth: None,
+            escaping: false,
+        }
+    }
+
+    pub fn theme(&self) -> Theme {
+        match self.current {
+            Some(i) => self.branches[i].theme,
+            None => Theme::Main,
+        }
+    }
+
+    pub fn next_branch(&self) -> Option<usize> {
+        match self.current {
+            Some(_) => None,
+            None if self.escaping => None,
+            None => self.branches.iter().position(|b| b.entry == self.main && !b.cleared),
+        }
+    }
+
+    pub fn has_stairs(&self) -> bool {
+        self.next_branch().is_some()
+    }
+
+    pub fn is_reward(&self) -> bool {
+        match self.current {
+            Some(i) => self.level == self.branches[i].length,
+            None => false,
+        }
+    }
+
+    pub fn is_surface(&self) -> bool {
+        self.current.is_none() && self.main <= 0
+    }
+
+    pub fn is_boss(&self) -> bool {
+        self.current.is_none() && self.main > 0 && !self.escaping && self.main % BOSS_INTERVAL == 0
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.current.is_none() && self.final_depth == Some(self.main)
+    }
+
+    pub fn difficulty(&self) -> i32 {
+        if self.escaping {
+            self.deepest*2 - self.main
+        } else {
+            self.depth()
+        }
+    }
+
+    pub fn depth(&self) -> i32 {
+        self.main + self.level
+    }
+
+    pub fn descend(&mut self, branch: bool) -> String {
+        if branch && self.has_stairs() {
+            self.current = self.next_branch();
+            self.level = 1;
+            return format!("You take the side stairs into the {}", self.theme().name())
+        }
+
+        match self.current {
+            Some(i) if self.level >= self.branches[i].length => {
+                self.branches[i].cleared = true;
+                self.current = None;
+                self.level = 0;
+                self.main += 1;
+                format!("You climb out of the {} back into the dungeon", self.branches[i].theme.name())
+            }
+            Some(_) => {
+                self.level += 1;
+                String::new()
+            }
+            None => {
+                self.main += 1;
+                if let Some(depth) = self.final_depth {
+                    self.main = self.main.min(depth);
+                }
+                self.deepest = self.deepest.max(self.main);
+                String::new()
+            }
+        }
+    }
+
+    pub fn ascend(&mut self) -> bool {
+        self.main -= 1;
+        self.main <= 0
+    }
+}
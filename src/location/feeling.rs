@@ -0,0 +1,80 @@
+use bracket_lib::{color::GOLD, random::RandomNumberGenerator};
+
+use crate::player::Player;
+use super::{branch::Dungeon, TileType, WorldLocation};
+
+const DANGER: [(i32, &str); 4] = [
+    (3, "You sense only a few weak creatures"),
+    (8, "You hear things moving in the dark"),
+    (15, "This place feels dangerous"),
+    (i32::MAX, "The stench of many monsters fills the air"),
+];
+
+const LOOT: [(i32, &str); 4] = [
+    (4, "There is little of value here"),
+    (8, "There may be a few trinkets around"),
+    (12, "You sense something worthwhile here"),
+    (i32::MAX, "You feel there is treasure nearby"),
+];
+
+fn blur(value: i32, skill: i32, rng: &mut RandomNumberGenerator) -> i32 {
+    let spread = 50 - skill*10;
+    value * (100 + rng.range(-spread, spread+1)) / 100
+}
+
+fn describe(value: i32, table: &[(i32, &str)]) -> String {
+    let (_, text) = table.iter().find(|(limit, _)| value < *limit).unwrap_or(&table[table.len()-1]);
+    String::from(*text)
+}
+
+impl WorldLocation {
+    pub fn feeling(&self, dungeon: &Dungeon, player: &Player) -> String {
+        if dungeon.is_surface() {
+            return String::new()
+        }
+
+        let mut rng = RandomNumberGenerator::new();
+        let area = self.width * self.height;
+        let place = match dungeon.current {
+            Some(_) => format!("You arrive in the {}, {} floors down", dungeon.theme().name(), dungeon.depth()),
+            None => format!("You arrive on floor {} of the dungeon", dungeon.main),
+        };
+
+        let monsters: Vec<_> = self.entities.iter().filter(|e| e.health > 0 && !e.is_friendly()).collect();
+        let mut danger = if self.boss().is_some() {
+            String::from("You feel a deadly presence")
+        } else if monsters.is_empty() {
+            String::from("It is eerily quiet")
+        } else {
+            let threat: i32 = monsters.iter().map(|e| e.damage*e.health/10).sum();
+            describe(blur(threat*1000/area, player.intelligence, &mut rng), &DANGER)
+        };
+        if player.intelligence >= 2 && monsters.len() >= 2 && self.boss().is_none() {
+            let mut names: Vec<String> = monsters.iter().map(|e| e.name()).collect();
+            names.sort();
+            let common = names.iter().max_by_key(|name| names.iter().filter(|n| n == name).count()).unwrap();
+            let plural = if common.ends_with('s') { "" } else { "s" };
+            danger = format!("{}, most of them {}{}", danger, common.to_lowercase(), plural);
+        }
+
+        let mut loot = 0;
+        let mut vault = false;
+        for (i, tile) in self.source.iter().enumerate() {
+            loot += match tile {
+                TileType::Chest | TileType::LockedChest if self.colors[i] == GOLD => {
+                    vault = true;
+                    9
+                }
+                TileType::Chest => 3,
+                TileType::LockedChest => 4,
+                TileType::Potion | TileType::Coin => 1,
+                _ => 0,
+            };
+        }
+        let mut lines = vec![place, danger, describe(blur(loot*1000/area, player.luck, &mut rng), &LOOT)];
+        if vault && player.luck >= 2 {
+            lines.push(String::from("Your luck tells you something precious is hidden here"));
+        }
+        lines.join("\n")
+    }
+}
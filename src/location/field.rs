@@ -0,0 +1,152 @@
+use bracket_lib::{color::DIM_GRAY, random::RandomNumberGenerator};
+
+use crate::player::Player;
+use super::{xy_idx, TileType, WorldLocation, SOLID_TILES};
+
+pub const GAS_HARM: u8 = 20;
+pub const GAS_THICK: u8 = 60;
+const GAS_DECAY: i32 = 2;
+
+impl WorldLocation {
+    pub fn update_fields(&mut self, player: &mut Player) -> Vec<String> {
+        let mut messages: Vec<String> = vec![];
+        let was_thick = self.gas.iter().any(|gas| *gas >= GAS_THICK);
+
+        self.spread_gas();
+        self.spread_fire();
+        self.update_scent(player);
+
+        let idx = xy_idx(player.pos.0, player.pos.1, self.width);
+        if self.gas[idx] >= GAS_HARM {
+            if player.poison == 0 {
+                messages.push(String::from("You breathe in the poison gas and feel sick"));
+            }
+            player.poison = player.poison.max(5);
+        }
+        if self.fire[idx] > 0 {
+            if player.burning == 0 {
+                messages.push(String::from("You catch fire!"));
+            }
+            player.burning = player.burning.max(3);
+        }
+
+        for entity in &mut self.entities {
+            if entity.health <= 0 {
+                continue;
+            }
+
+            let idx = xy_idx(entity.x, entity.y, self.width);
+            if self.fire[idx] > 0 {
+                entity.health -= 4;
+                if entity.health <= 0 {
+                    messages.push(format!("{} burns to death", entity.name()));
+                }
+            }
+            if self.gas[idx] >= GAS_HARM {
+                entity.health -= 1 + self.gas[idx] as i32 / 64;
+                if entity.health <= 0 {
+                    messages.push(format!("{} chokes on the gas and dies", entity.name()));
+                }
+            }
+            if entity.health <= 0 {
+                self.occupants[idx] = None;
+            }
+        }
+
+        if was_thick || self.gas.iter().any(|gas| *gas >= GAS_THICK) {
+            self.dirty = true;
+        }
+        messages
+    }
+
+    pub fn release_gas(&mut self, (x, y): (i32, i32)) {
+        for yy in y-1..=y+1 {
+            for xx in x-1..=x+1 {
+                let idx = xy_idx(xx, yy, self.width);
+                if self.is_open(idx) {
+                    self.gas[idx] = 255;
+                }
+            }
+        }
+    }
+
+    pub fn is_flammable(&self, idx: usize) -> bool {
+        matches!(self.source[idx], TileType::Door | TileType::Chest | TileType::LockedChest)
+    }
+
+    pub fn ignite(&mut self, idx: usize) {
+        if (self.is_flammable(idx) || self.is_open(idx)) && self.fire[idx] == 0 {
+            self.fire[idx] = match self.source[idx] {
+                TileType::Door => 6,
+                TileType::Chest | TileType::LockedChest => 4,
+                _ => 2,
+            };
+        }
+    }
+
+    pub fn neighbours(&self, idx: usize) -> Vec<usize> {
+        let x = idx as i32 % self.width;
+        let y = idx as i32 / self.width;
+        [(x+1, y), (x-1, y), (x, y+1), (x, y-1)].iter()
+            .filter(|(x, y)| *x >= 0 && *y >= 0 && *x < self.width && *y < self.height)
+            .map(|(x, y)| xy_idx(*x, *y, self.width))
+            .collect()
+    }
+
+    fn spread_gas(&mut self) {
+        let mut gas: Vec<i32> = self.gas.iter().map(|gas| *gas as i32).collect();
+        for idx in 0..self.gas.len() {
+            if self.gas[idx] == 0 {
+                continue;
+            }
+
+            let open: Vec<usize> = self.neighbours(idx).into_iter()
+                .filter(|i| self.is_open(*i))
+                .collect();
+            let share = self.gas[idx] as i32 / 6;
+            for i in &open {
+                gas[*i] += share;
+            }
+            gas[idx] -= share * open.len() as i32;
+        }
+
+        for (idx, value) in gas.iter().enumerate() {
+            self.gas[idx] = (value - GAS_DECAY).clamp(0, 255) as u8;
+        }
+    }
+
+    fn spread_fire(&mut self) {
+        let mut rng = RandomNumberGenerator::new();
+        for idx in 0..self.fire.len() {
+            if self.source[idx] == TileType::Lava {
+                for i in self.neighbours(idx) {
+                    if rng.range(1, 101) <= 1 {
+                        self.ignite(i);
+                    }
+                }
+            }
+        }
+
+        let burning: Vec<usize> = (0..self.fire.len()).filter(|idx| self.fire[*idx] > 0).collect();
+        for idx in burning {
+            for i in self.neighbours(idx) {
+                if self.is_flammable(i) && rng.range(1, 101) <= 25 {
+                    self.ignite(i);
+                }
+            }
+
+            self.fire[idx] -= 1;
+            if self.fire[idx] == 0 && self.is_flammable(idx) {
+                if matches!(self.source[idx], TileType::Door | TileType::LockedChest) {
+                    self.dirty = true;
+                }
+                self.source[idx] = TileType::Ash;
+                self.colors[idx] = DIM_GRAY;
+            }
+        }
+    }
+
+    fn is_open(&self, idx: usize) -> bool {
+        !SOLID_TILES.contains(&self.source[idx])
+    }
+}
@@ -0,0 +1,127 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use crate::player::Player;
+use super::{xy_idx, WorldLocation};
+
+const FLEE_FACTOR: f32 = -1.2;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Goal {
+    Pursuit,
+    Smash,
+    Flee,
+    Items,
+}
+
+#[derive(Clone, Default)]
+pub struct Flow {
+    pursuit: Vec<f32>,
+    smash: Vec<f32>,
+    flee: Vec<f32>,
+    items: Vec<f32>,
+}
+
+impl Flow {
+    fn get(&self, goal: Goal) -> &[f32] {
+        match goal {
+            Goal::Pursuit => &self.pursuit,
+            Goal::Smash => &self.smash,
+            Goal::Flee => &self.flee,
+            Goal::Items => &self.items,
+        }
+    }
+}
+
+#[derive(PartialEq)]
+struct Frontier(f32, usize);
+
+impl Eq for Frontier {}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl WorldLocation {
+    fn relax(&self, mut field: Vec<f32>, smash: bool) -> Vec<f32> {
+        let mut open: BinaryHeap<Frontier> = (0..field.len())
+            .filter(|idx| field[*idx] < f32::MAX)
+            .map(|idx| Frontier(field[idx], idx))
+            .collect();
+        while let Some(Frontier(depth, idx)) = open.pop() {
+            if depth > field[idx] {
+                continue;
+            }
+            for (next, cost) in self.exits(idx, smash, false) {
+                if depth + cost < field[next] {
+                    field[next] = depth + cost;
+                    open.push(Frontier(field[next], next));
+                }
+            }
+        }
+        field
+    }
+
+    fn flow_from(&self, starts: impl Iterator<Item = usize>, smash: bool) -> Vec<f32> {
+        let mut field = vec![f32::MAX; self.source.len()];
+        for idx in starts {
+            field[idx] = 0.0;
+        }
+        self.relax(field, smash)
+    }
+
+    pub fn update_flow(&mut self, player: &Player) {
+        let target = xy_idx(player.pos.0, player.pos.1, self.width);
+        self.flow.pursuit = self.flow_from([target].into_iter(), false);
+
+        self.flow.smash = vec![];
+        if self.entities.iter().any(|e| e.health > 0 && e.can_smash()) {
+            self.flow.smash = self.flow_from([target].into_iter(), true);
+        }
+
+        self.flow.items = vec![];
+        if !self.items.is_empty() {
+            self.flow.items = self.flow_from(self.items.keys().copied(), false);
+        }
+
+        self.flow.flee = vec![];
+        if self.entities.iter().any(|e| e.health > 0 && e.is_fleeing()) {
+            let field = self.flow.pursuit.iter()
+                .map(|depth| if *depth < f32::MAX { depth*FLEE_FACTOR } else { f32::MAX })
+                .collect();
+            self.flow.flee = self.relax(field, false);
+        }
+    }
+
+    pub fn flow_at(&self, goal: Goal, idx: usize) -> f32 {
+        self.flow.get(goal).get(idx).copied().unwrap_or(f32::MAX)
+    }
+
+    pub fn blocker(&self, goal: Goal, idx: usize) -> Option<usize> {
+        self.lowest_exit(goal, idx, false)
+            .filter(|next| self.occupants[*next].is_some_and(|j| j < self.entities.len()))
+    }
+
+    pub fn step_down(&self, goal: Goal, idx: usize) -> Option<usize> {
+        self.lowest_exit(goal, idx, true)
+    }
+
+    fn lowest_exit(&self, goal: Goal, idx: usize, crowded: bool) -> Option<usize> {
+        let field = self.flow.get(goal);
+        if field.is_empty() {
+            return None
+        }
+
+        self.exits(idx, goal == Goal::Smash, crowded).iter()
+            .map(|(next, _)| *next)
+            .filter(|next| field[*next] < field[idx])
+            .min_by(|a, b| field[*a].total_cmp(&field[*b]))
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use bracket_lib::{color::{GRAY, GREEN3, GREEN4, GREENYELLOW, ORANGE, ORANGE_RED, RGB, WHITE}, pathfinding::a_star_search, random::RandomNumberGenerator, terminal::BTerm};
+
+use crate::{entity::{Entity, EntityType}, player::{ItemType, Material, Size, WeaponType}};
+use super::{flow::Flow, trap::TrapType, xy_idx, TileType, WorldLocation};
+
+pub const FOREST_WIDTH: i32 = 64;
+pub const FOREST_HEIGHT: i32 = 26;
+const CLEARING: i32 = 5;
+
+fn wares(kind: i32) -> Vec<(ItemType, i32)> {
+    match kind {
+        0 => vec![
+            (ItemType::Potion(Size::Small, ORANGE_RED), 2),
+            (ItemType::Potion(Size::Average, ORANGE_RED), 4),
+            (ItemType::Potion(Size::Large, ORANGE_RED), 7),
+        ],
+        1 => vec![
+            (ItemType::Weapon(WeaponType::Dagger, Material::Silver, 2), 5),
+            (ItemType::Weapon(WeaponType::Sword, Material::Bronze, 3), 8),
+            (ItemType::Weapon(WeaponType::Mace, Material::Bronze, 3), 9),
+            (ItemType::Weapon(WeaponType::Axe, Material::Bronze, 4), 12),
+        ],
+        _ => vec![
+            (ItemType::TrapKit(TrapType::Alarm), 3),
+            (ItemType::TrapKit(TrapType::Bear), 4),
+            (ItemType::TrapKit(TrapType::Gas), 5),
+        ],
+    }
+}
+
+impl WorldLocation {
+    pub fn forest(engine: &mut BTerm, width: i32, height: i32, exp: i32) -> Self {
+        let mut rng = RandomNumberGenerator::new();
+        let mut map = vec![TileType::Floor; (width * height) as usize];
+        let mut colors = vec![GRAY; (width * height) as usize];
+        let mut entities = vec![];
+        engine.screen_burn_color(RGB::from_u8(GREEN4.0, GREEN4.1, GREEN4.2));
+
+        for x in 0..width {
+            map[xy_idx(x, 0, width)] = TileType::Tree1;
+            map[xy_idx(x, height-1, width)] = TileType::Tree1;
+        }
+        for y in 0..height {
+            map[xy_idx(0, y, width)] = TileType::Tree1;
+            map[xy_idx(width-1, y, width)] = TileType::Tree1;
+        }
+
+        for _i in 0..rng.range(350, 1001) {
+            let idx = xy_idx(rng.roll_dice(1, width-1), rng.roll_dice(1, height-1), width);
+            map[idx] = if rng.range(1, 3) == 1 { TileType::Tree1 } else { TileType::Tree2 };
+        }
+
+        let (cx, cy) = (width/2, height/2);
+        let entrance = (rng.range(3, width-3), if rng.range(1, 3) == 1 { 3 } else { height-4 });
+        for (x, y, radius) in [(cx, cy, CLEARING), (entrance.0, entrance.1, 2)] {
+            for yy in y-radius..=y+radius {
+                for xx in x-radius..=x+radius {
+                    if (xx-x).pow(2) + (yy-y).pow(2) <= radius*radius {
+                        map[xy_idx(xx, yy, width)] = TileType::Floor;
+                    }
+                }
+            }
+        }
+
+        for i in 0..map.len() {
+            if map[i] != TileType::Floor {
+                colors[i] = match rng.roll_dice(2, 6) {
+                    2..=7 => GREEN4,
+                    8..=9 => GREEN3,
+                    _ => GREENYELLOW,
+                };
+            }
+        }
+
+        map[xy_idx(cx, cy-2, width)] = TileType::Campfire;
+        colors[xy_idx(cx, cy-2, width)] = ORANGE;
+        map[xy_idx(entrance.0, entrance.1, width)] = TileType::Exit;
+        colors[xy_idx(entrance.0, entrance.1, width)] = WHITE;
+
+        for (kind, (x, y)) in [(cx-3, cy+2), (cx, cy+3), (cx+3, cy+2)].into_iter().enumerate() {
+            let mut trader = Entity::new(x, y, EntityType::Trader, exp);
+            trader.stock = wares(kind as i32);
+            entities.push(trader);
+        }
+        for _i in 0..rng.range(2, 5) {
+            let (x, y) = (rng.range(1, width-1), rng.range(1, height-1));
+            if map[xy_idx(x, y, width)] == TileType::Floor &&
+                (x-cx).pow(2) + (y-cy).pow(2) > CLEARING*CLEARING {
+                entities.push(Entity::new(x, y, EntityType::Human, exp));
+            }
+        }
+
+        let mut result = Self {
+            durability: vec![0; map.len()],
+            gas: vec![0; map.len()],
+            fire: vec![0; map.len()],
+            scent: vec![0; map.len()],
+            doorways: vec![],
+            mechanisms: vec![],
+            portals: vec![],
+            items: HashMap::new(),
+            purses: HashMap::new(),
+            occupants: vec![],
+            summons: vec![],
+            prompts: vec![],
+            sealed: None,
+            flow: Flow::default(),
+            source: map,
+            entities,
+            colors,
+            width,
+            height,
+            start_x: cx,
+            start_y: cy,
+            smash: false,
+            dirty: false,
+            alarm: false,
+            turn: 0,
+        };
+        result.index_entities();
+
+        if !a_star_search(xy_idx(cx, cy, width), xy_idx(entrance.0, entrance.1, width), &result).success {
+            return WorldLocation::forest(engine, width, height, exp);
+        }
+        result
+    }
+}
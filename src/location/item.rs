@@ -0,0 +1,66 @@
+use bracket_lib::color::CYAN;
+
+use crate::player::{ItemType, Player};
+use super::{xy_idx, TileType, WorldLocation};
+
+pub const LURE_RADIUS: i32 = 8;
+
+impl WorldLocation {
+    pub fn drop_item(&mut self, idx: usize, item: ItemType) -> bool {
+        if !matches!(self.source[idx], TileType::Floor | TileType::BloodStain | TileType::Ash | TileType::Item) {
+            return false;
+        }
+
+        self.source[idx] = TileType::Item;
+        self.colors[idx] = CYAN;
+        self.items.entry(idx).or_default().push(item);
+        true
+    }
+
+    pub fn items_at(&self, idx: usize) -> Vec<&ItemType> {
+        self.items.get(&idx).map(|items| items.iter().collect()).unwrap_or_default()
+    }
+
+    pub fn take_items(&mut self, idx: usize) -> Vec<ItemType> {
+        let taken = self.items.remove(&idx).unwrap_or_default();
+        if self.source[idx] == TileType::Item {
+            self.source[idx] = TileType::Floor;
+        }
+        taken
+    }
+
+    pub fn item_symbol(&self, idx: usize) -> char {
+        match self.items_at(idx).first() {
+            Some(ItemType::Potion(_, _)) => '!',
+            Some(ItemType::Weapon(_, _, _)) => ')',
+            _ => '&',
+        }
+    }
+
+    pub fn drop_carried(&mut self, player: &mut Player) -> Vec<String> {
+        let mut messages = vec![];
+        for i in 0..self.entities.len() {
+            if self.entities[i].health > 0 || self.entities[i].is_bones() {
+                continue;
+            }
+
+            let (x, y) = (self.entities[i].x, self.entities[i].y);
+            let idx = xy_idx(x, y, self.width);
+            let mut dropped = vec![];
+            for item in std::mem::take(&mut self.entities[i].carried) {
+                let name = Player::item_name(&item);
+                let spot = std::iter::once(idx).chain(self.neighbours(idx))
+                    .find(|i| matches!(self.source[*i], TileType::Floor | TileType::BloodStain | TileType::Ash | TileType::Item));
+                if let Some(spot) = spot {
+                    self.drop_item(spot, item);
+                    dropped.push(name);
+                }
+            }
+            dropped.extend(self.drop_purse(i, player));
+            if !dropped.is_empty() && player.sees((x, y)) {
+                messages.push(format!("{} drops {}", self.entities[i].name(), dropped.join(", ")));
+            }
+        }
+        messages
+    }
+}
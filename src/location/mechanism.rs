@@ -0,0 +1,281 @@
+use std::collections::VecDeque;
+
+use bracket_lib::{color::{GOLD, GRAY, STEEL_BLUE}, random::RandomNumberGenerator, terminal::{DistanceAlg::Pythagoras, Point}};
+
+use crate::player::Player;
+use super::{xy_idx, Room, TileType, WorldLocation, SOLID_TILES};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Trigger {
+    Lever,
+    Plate,
+}
+
+#[derive(Clone, Debug)]
+pub struct Mechanism {
+    pub trigger: usize,
+    pub kind: Trigger,
+    pub gates: Vec<usize>,
+    pub timer: i32,
+    countdown: i32,
+    open: bool,
+}
+
+impl Mechanism {
+    pub fn new(trigger: usize, kind: Trigger, gates: Vec<usize>, timer: i32) -> Self {
+        Self {
+            trigger,
+            kind,
+            gates,
+            timer,
+            countdown: 0,
+            open: false,
+        }
+    }
+}
+
+impl WorldLocation {
+    pub fn update_mechanisms(&mut self, player: &Player) -> Vec<String> {
+        let mut messages: Vec<String> = vec![];
+        let player_idx = xy_idx(player.pos.0, player.pos.1, self.width);
+
+        for i in 0..self.mechanisms.len() {
+            let trigger = self.mechanisms[i].trigger;
+            match self.mechanisms[i].kind {
+                Trigger::Plate => {
+                    let held = player_idx == trigger || self.source[trigger] == TileType::Boulder ||
+                        self.occupants[trigger].is_some();
+
+                    if held {
+                        self.mechanisms[i].countdown = self.mechanisms[i].timer;
+                        if !self.mechanisms[i].open {
+                            self.set_gates(i, true, player_idx);
+                            messages.push(String::from("The plate sinks with a click and a gate grinds open"));
+                        }
+                    } else if self.mechanisms[i].open {
+                        if self.mechanisms[i].countdown > 0 {
+                            self.mechanisms[i].countdown -= 1;
+                        } else if self.set_gates(i, false, player_idx) {
+                            messages.push(String::from("You hear a gate slam shut"));
+                        }
+                    }
+                }
+                Trigger::Lever => {
+                    if self.mechanisms[i].timer > 0 && self.mechanisms[i].open {
+                        self.mechanisms[i].countdown -= 1;
+                        if self.mechanisms[i].countdown <= 0 && self.set_gates(i, false, player_idx) {
+                            self.source[trigger] = TileType::Lever;
+                            messages.push(String::from("The lever snaps back and a gate slams shut"));
+                        }
+                    }
+                }
+            }
+        }
+        messages
+    }
+
+    pub fn pull_lever(&mut self, idx: usize, player: &Player) -> String {
+        let player_idx = xy_idx(player.pos.0, player.pos.1, self.width);
+        let i = match self.mechanisms.iter().position(|m| m.trigger == idx) {
+            Some(i) => i,
+            None => return String::from("The lever is rusted in place"),
+        };
+
+        if !self.mechanisms[i].open {
+            self.set_gates(i, true, player_idx);
+            self.mechanisms[i].countdown = self.mechanisms[i].timer;
+            self.source[idx] = TileType::LeverPulled;
+            if self.mechanisms[i].timer > 0 {
+                String::from("You pull the lever and hear a gate open, the mechanism is ticking...")
+            } else {
+                String::from("You pull the lever and hear a gate open")
+            }
+        } else if self.mechanisms[i].timer > 0 {
+            String::from("The lever won't move until the mechanism winds down")
+        } else if self.set_gates(i, false, player_idx) {
+            self.source[idx] = TileType::Lever;
+            String::from("You push the lever back and hear a gate close")
+        } else {
+            String::from("Something is blocking the gate")
+        }
+    }
+
+    pub fn push_boulder(&mut self, from: usize, to: usize) -> bool {
+        let (x, y) = (to as i32 % self.width, to as i32 / self.width);
+        if self.is_solid((x, y)) ||
+            !matches!(self.source[to], TileType::Floor | TileType::PressurePlate | TileType::BloodStain | TileType::Ash) {
+            return false;
+        }
+
+        self.source[to] = TileType::Boulder;
+        self.colors[to] = self.colors[from];
+        if self.mechanisms.iter().any(|m| m.kind == Trigger::Plate && m.trigger == from) {
+            self.source[from] = TileType::PressurePlate;
+            self.colors[from] = GRAY;
+        } else {
+            self.source[from] = TileType::Floor;
+        }
+        true
+    }
+
+    fn set_gates(&mut self, i: usize, open: bool, player_idx: usize) -> bool {
+        let gates = self.mechanisms[i].gates.clone();
+        if !open && gates.iter().any(|gate| *gate == player_idx || self.occupants[*gate].is_some()) {
+            return false;
+        }
+
+        for gate in gates {
+            self.source[gate] = if open { TileType::GateOpen } else { TileType::Gate };
+        }
+        self.mechanisms[i].open = open;
+        true
+    }
+
+    pub fn apply_puzzle(rooms: &[Room], map: &mut [TileType], colors: &mut [(u8, u8, u8)],
+        mechanisms: &mut Vec<Mechanism>, width: i32, rng: &mut RandomNumberGenerator, goals: &[usize]) {
+        let g = rng.range(2, rooms.len() as i32) as usize;
+        let t = if rng.range(1, 3) == 1 { g - 1 } else { (g + 1) % rooms.len() };
+        let (guarded, trigger_room) = (rooms[g], rooms[t]);
+        let snapshot = map.to_vec();
+        let mut boulder = None;
+
+        let mut gates = vec![];
+        for (x, y) in guarded.ring() {
+            let idx = xy_idx(x, y, width);
+            if map[idx] == TileType::SecretDoor {
+                return;
+            } else if !matches!(map[idx], TileType::Wall | TileType::WeakWall | TileType::DamagedWall) {
+                gates.push(idx);
+            }
+        }
+        if gates.is_empty() {
+            return;
+        }
+
+        let distance = Pythagoras.distance2d(Point::new(guarded.center().0, guarded.center().1),
+            Point::new(trigger_room.center().0, trigger_room.center().1)) as i32;
+        let (kind, trigger, timer) = match rng.range(1, 5) {
+            1 | 2 => {
+                let mut lever = None;
+                for _i in 0..20 {
+                    let y = rng.range(trigger_room.y1 + 1, trigger_room.y2 + 1);
+                    let idx = xy_idx(trigger_room.x1, y, width);
+                    if map[idx] == TileType::Wall && map[idx + 1] == TileType::Floor {
+                        lever = Some(idx);
+                        break;
+                    }
+                }
+                match lever {
+                    Some(idx) => (Trigger::Lever, idx, if rng.range(1, 3) == 1 { distance*2 + 10 } else { 0 }),
+                    None => return,
+                }
+            }
+            3 => {
+                let (x, y) = (trigger_room.x1 + 2, trigger_room.y1 + 2);
+                if (0..4).any(|i| map[xy_idx(x + i, y, width)] != TileType::Floor) {
+                    return;
+                }
+                map[xy_idx(x + 2, y, width)] = TileType::Boulder;
+                boulder = Some(xy_idx(x + 2, y, width));
+                (Trigger::Plate, xy_idx(x, y, width), 0)
+            }
+            _ => {
+                let (x, y) = (trigger_room.x1 + 2, trigger_room.y1 + 2);
+                if map[xy_idx(x, y, width)] != TileType::Floor {
+                    return;
+                }
+                (Trigger::Plate, xy_idx(x, y, width), distance*2 + 10)
+            }
+        };
+
+        map[trigger] = match kind {
+            Trigger::Lever => TileType::Lever,
+            Trigger::Plate => TileType::PressurePlate,
+        };
+        for gate in &gates {
+            map[*gate] = TileType::Gate;
+        }
+
+        let mut chest = None;
+        for _i in 0..20 {
+            let (x, y) = (rng.range(guarded.x1 + 1, guarded.x2 + 1), rng.range(guarded.y1 + 1, guarded.y2 + 1));
+            if map[xy_idx(x, y, width)] == TileType::Floor {
+                map[xy_idx(x, y, width)] = TileType::Chest;
+                chest = Some(xy_idx(x, y, width));
+                break;
+            }
+        }
+
+        mechanisms.push(Mechanism::new(trigger, kind, gates.clone(), timer));
+        let mut targets = goals.to_vec();
+        targets.extend(chest);
+        if !WorldLocation::is_solvable(map, mechanisms, width, goals[0], &targets) {
+            mechanisms.pop();
+            map.copy_from_slice(&snapshot);
+            return;
+        }
+
+        colors[trigger] = match kind {
+            Trigger::Lever => GOLD,
+            Trigger::Plate => GRAY,
+        };
+        for gate in &gates {
+            colors[*gate] = STEEL_BLUE;
+        }
+        if let Some(idx) = chest {
+            colors[idx] = GOLD;
+        }
+        if let Some(idx) = boulder {
+            colors[idx] = GRAY;
+        }
+    }
+
+    pub fn is_solvable(map: &[TileType], mechanisms: &[Mechanism], width: i32, start: usize, goals: &[usize]) -> bool {
+        let mut open = vec![false; mechanisms.len()];
+        loop {
+            let reachable = WorldLocation::flood(map, mechanisms, &open, width, start);
+            let mut changed = false;
+            for (i, mechanism) in mechanisms.iter().enumerate() {
+                let t = mechanism.trigger;
+                if !open[i] && (reachable[t] || [t - 1, t + 1, t - width as usize, t + width as usize]
+                    .iter().any(|n| reachable[*n])) {
+                    open[i] = true;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                return goals.iter().all(|goal| reachable[*goal]);
+            }
+        }
+    }
+
+    fn flood(map: &[TileType], mechanisms: &[Mechanism], open: &[bool], width: i32, start: usize) -> Vec<bool> {
+        let mut reachable = vec![false; map.len()];
+        let mut queue = VecDeque::from([start]);
+        reachable[start] = true;
+
+        while let Some(idx) = queue.pop_front() {
+            let (x, y) = (idx as i32 % width, idx as i32 / width);
+            for (nx, ny) in [(x+1, y), (x-1, y), (x, y+1), (x, y-1)] {
+                if nx < 0 || ny < 0 || nx >= width || ny as usize >= map.len() / width as usize {
+                    continue;
+                }
+
+                let n = xy_idx(nx, ny, width);
+                let passable = match map[n] {
+                    TileType::Gate => mechanisms.iter().enumerate()
+                        .any(|(i, m)| open[i] && m.gates.contains(&n)),
+                    TileType::Door => true,
+                    TileType::Chasm | TileType::Lava => false,
+                    tile => !SOLID_TILES.contains(&tile),
+                };
+                if passable && !reachable[n] {
+                    reachable[n] = true;
+                    queue.push_back(n);
+                }
+            }
+        }
+        reachable
+    }
+}
@@ -0,0 +1,919 @@
+#[derive(PartialEq, Copy, Clone)]
+pub enum TileType {
+    Wall,
+    Floor,
+    Exit,
+    Potion,
+    Coin,
+    Trap(TrapType),
+    TrapFound(TrapType),
+    BearTrapActived,
+    BloodStain,
+    Door,
+    Chest,
+    LockedChest,
+    SecretDoor,
+    WeakWall,
+    DamagedWall,
+    Rubble,
+    DeepWater,
+    ShallowWater,
+    Lava,
+    Chasm,
+    Bridge,
+    Mud,
+    Ash,
+    Lever,
+    LeverPulled,
+    PressurePlate,
+    Gate,
+    GateOpen,
+    Boulder,
+    Teleporter,
+    TeleporterHidden,
+    TrapSet(TrapType),
+    Item,
+    BranchStair,
+    SealedExit,
+    Artifact,
+    UpStair,
+    Tree1,
+    Tree2,
+    Campfire,
+}
+
+const SOLID_TILES: [TileType; 14] = [
+    TileType::Wall,
+    TileType::Door,
+    TileType::LockedChest,
+    TileType::SecretDoor,
+    TileType::WeakWall,
+    TileType::DamagedWall,
+    TileType::Lever,
+    TileType::LeverPulled,
+    TileType::Gate,
+    TileType::Boulder,
+    TileType::SealedExit,
+    TileType::Tree1,
+    TileType::Tree2,
+    TileType::Campfire,
+];
+
+const TRANSPARENT_TILES: [TileType; 30] = [
+    TileType::Floor,
+    TileType::BearTrapActived,
+    TileType::BloodStain,
+    TileType::Potion,
+    TileType::Exit,
+    TileType::Coin,
+    TileType::Chest,
+    TileType::LockedChest,
+    TileType::Rubble,
+    TileType::DeepWater,
+    TileType::ShallowWater,
+    TileType::Lava,
+    TileType::Chasm,
+    TileType::Bridge,
+    TileType::Mud,
+    TileType::Ash,
+    TileType::PressurePlate,
+    TileType::Gate,
+    TileType::GateOpen,
+    TileType::Boulder,
+    TileType::Teleporter,
+    TileType::TeleporterHidden,
+    TileType::Item,
+    TileType::BranchStair,
+    TileType::SealedExit,
+    TileType::Artifact,
+    TileType::UpStair,
+    TileType::Tree1,
+    TileType::Tree2,
+    TileType::Campfire,
+];
+
+pub mod arena;
+pub mod branch;
+pub mod feeling;
+pub mod field;
+pub mod flow;
+pub mod forest;
+pub mod item;
+pub mod mechanism;
+pub mod occupancy;
+pub mod portal;
+pub mod scent;
+pub mod trap;
+pub mod tutorial;
+
+use std::{cmp::{max, min}, collections::HashMap, vec};
+
+use bracket_lib::{color::{ALICE_BLUE, BLUE, BURLYWOOD, CHARTREUSE, CYAN, DIM_GRAY, DODGER_BLUE, GOLD, GREY100, HOT_PINK, LIGHT_BLUE, MAGENTA, ORANGE, ORANGE_RED, RGB, ROSY_BROWN, SADDLE_BROWN, TOMATO, WHITE, WHITESMOKE}, pathfinding::{a_star_search, Algorithm2D, BaseMap, SmallVec}, random::RandomNumberGenerator, terminal::{BTerm, Point, GRAY, GRAY100, GREEN3, PINK, SANDY_BROWN, YELLOW3, YELLOWGREEN}};
+use bracket_lib::terminal::DistanceAlg::Pythagoras;
+
+use crate::{entity::EntityType, player::{ItemType, Player}, State};
+use super::entity::Entity;
+use self::{branch::{Dungeon, Theme}, field::{GAS_HARM, GAS_THICK}, flow::Flow, forest::{FOREST_HEIGHT, FOREST_WIDTH}, mechanism::Mechanism, trap::TrapType, tutorial::PromptTrigger};
+
+pub fn xy_idx(x: i32, y: i32, width: i32) -> usize {
+    (y as usize * width as usize) + x as usize
+}
+
+#[derive(Clone)]
+pub struct WorldLocation {
+    pub source: Vec<TileType>,
+    pub entities: Vec<Entity>,
+    pub colors: Vec<(u8, u8, u8)>,
+    pub width: i32,
+    pub height: i32,
+    pub start_x: i32,
+    pub start_y: i32,
+    pub smash: bool,
+    pub dirty: bool,
+    pub alarm: bool,
+    pub durability: Vec<i32>,
+    pub gas: Vec<u8>,
+    pub fire: Vec<u8>,
+    pub scent: Vec<u8>,
+    pub doorways: Vec<usize>,
+    pub mechanisms: Vec<Mechanism>,
+    pub portals: Vec<(usize, usize)>,
+    pub items: HashMap<usize, Vec<ItemType>>,
+    pub purses: HashMap<usize, i32>,
+    pub occupants: Vec<Option<usize>>,
+    pub summons: Vec<Entity>,
+    pub prompts: Vec<(PromptTrigger, &'static str)>,
+    pub sealed: Option<(usize, TileType)>,
+    pub flow: Flow,
+    pub turn: usize,
+}
+
+const MAX_ROOMS: i32 = 100000;
+const MIN_SIZE: i32 = 6;
+const MAX_SIZE: i32 = 15;
+const VAULT_SIZE: i32 = 9;
+const WALL_DURABILITY: i32 = 30;
+const WEAK_WALL_DURABILITY: i32 = 12;
+
+impl WorldLocation {
+    pub fn new(engine: &mut BTerm, width: i32, height: i32, exp: i32, floor: i32, dungeon: &Dungeon) -> Self { 
+        if dungeon.is_surface() {
+            return WorldLocation::forest(engine, FOREST_WIDTH, FOREST_HEIGHT, exp);
+        }
+        if dungeon.is_boss() {
+            return WorldLocation::arena(engine, width, height, exp, dungeon.main, dungeon.is_final());
+        }
+
+        let mut map = vec![TileType::Floor; (width * height) as usize];
+        let mut color = vec![GRAY; (width * height) as usize];
+        let mut entities: Vec<Entity> = vec![];
+
+        let mut rng = RandomNumberGenerator::new();
+        for i in 0..map.len() {
+            map[i] = TileType::Wall;
+        }
+
+        let theme = dungeon.theme();
+        let palette = match theme {
+            Theme::Main => rng.range(1, 11),
+            Theme::Crypt => 1,
+            Theme::Warrens => 4,
+            Theme::Flooded => 10,
+        };
+        let colors = match palette {
+            0..=3 => (SANDY_BROWN, ROSY_BROWN, GRAY100, GRAY100),
+            4..=6 => (YELLOWGREEN, YELLOW3, GREEN3, PINK),
+            7..=9 => (GREY100, GRAY, ORANGE_RED, TOMATO),
+            _ => (LIGHT_BLUE, ALICE_BLUE, CYAN, GREY100),
+        };
+        engine.screen_burn_color(RGB::from_u8(colors.0.0, colors.0.1, 
+            colors.0.1));
+
+        for i in 0..color.len() {
+            color[i] = match rng.roll_dice(2, 6) {
+                (1..=6) => colors.0,
+                (7..=9) => colors.1,
+                10 => colors.2,
+                _ => colors.3,
+            };
+        }
+
+
+        let mut rooms: Vec<Room> = Vec::new();
+        for i in 0..MAX_ROOMS {
+            let w = rng.range(MIN_SIZE, MAX_SIZE);
+            let h = rng.range(MIN_SIZE, MAX_SIZE);
+            let x = rng.roll_dice(1, width - w - 1) - 1;
+            let y = rng.roll_dice(1, height - h - 1) - 1;
+            let new_room = Room::new(x, y, w, h);
+            let mut ok = true;
+            for other_room in rooms.iter() {
+                if new_room.intersect(other_room) {
+                    ok = false;
+                }
+            }
+            if ok {
+                WorldLocation::apply_room_to_map(&new_room, &mut map, width);
+
+                if !rooms.is_empty() {
+                    let (new_x, new_y) = new_room.center();
+                    let (prev_x, prev_y) = rooms[rooms.len() - 1].center();
+                    if rng.range(1, 3) == 1 {
+                        WorldLocation::apply_horizontal_tunnel(
+                            &mut map,
+                            &mut color,
+                            prev_x,
+                            new_x,
+                            prev_y,
+                            width,
+                            height,
+                            &mut rng,
+                        );
+                        WorldLocation::apply_vertical_tunnel(
+                            &mut map,
+                            &mut color,
+                            prev_y,
+                            new_y,
+                            new_x,
+                            width,
+                            height,
+                            &mut rng,
+                        );
+                    } else {
+                        WorldLocation::apply_vertical_tunnel(
+                            &mut map,
+                            &mut color,
+                            prev_y,
+                            new_y,
+                            prev_x,
+                            width,
+                            height,
+                            &mut rng,
+                        );
+                        WorldLocation::apply_horizontal_tunnel(
+                            &mut map,
+                            &mut color,
+                            prev_x,
+                            new_x,
+                            new_y,
+                            width,
+                            height,
+                            &mut rng,
+                        );
+                    }
+                }
+                WorldLocation::generate_at_room(&mut map, &mut color, 
+                    (new_room.x1, new_room.y1),
+                    (new_room.x2, new_room.y2),
+                    width,
+                    &mut rng, floor);
+                rooms.push(new_room);
+
+                let t = match colors.0 {
+                    LIGHT_BLUE if theme == Theme::Flooded => EntityType::Zombie,
+                    SANDY_BROWN if theme == Theme::Crypt => EntityType::Skeleton,
+                    GRAY100 => {
+                        if rng.range(1, 101) <= 50 {
+                            EntityType::Cyclops
+                        } else {
+                            EntityType::Orc
+                        }
+                    }
+                    SANDY_BROWN => {
+                        if rng.range(1, 101) <= 50 {
+                            EntityType::Skeleton
+                        } else {
+                            EntityType::Zombie
+                        }
+                    }
+                    _ => {
+                        if rng.range(1, 101) <= 25 {
+                            EntityType::Bandit
+                        } else {
+                            EntityType::Goblin
+                        }
+                    }
+                };
+
+                let spawn = if theme == Theme::Warrens { floor+60 } else { floor+35 };
+                if rng.range(1, 101) <= spawn && i > 1 {
+                    entities.push(Entity::new(new_room.center().0, new_room.center().1, t, exp));
+                }
+            }
+        }
+        for room in rooms.iter().skip(2) {
+            if theme == Theme::Flooded && rng.range(1, 101) <= 60 {
+                WorldLocation::apply_terrain(room, &mut map, &mut color, width, 0, &mut rng);
+            } else if rng.range(1, 101) <= 20 {
+                let kind = rng.range(0, if floor >= 2 { 4 } else { 2 });
+                WorldLocation::apply_terrain(room, &mut map, &mut color, width, kind, &mut rng);
+            }
+        }
+        for i in &entities {
+            map[xy_idx(i.x, i.y, width)] = TileType::Floor;
+        }
+
+        let mut vaults = vec![];
+        for room in rooms.iter().skip(2) {
+            if room.x2 - room.x1 <= VAULT_SIZE && room.y2 - room.y1 <= VAULT_SIZE && rng.range(1, 101) <= 15 {
+                WorldLocation::apply_vault(room, &mut map, &mut color, width, &mut rng);
+                vaults.push(room);
+            }
+        }
+
+        
+        for i in 0..map.len() {
+            let x = i as i32 % width;
+            let y = i as i32 / width;
+
+            if map[i] == TileType::Door {
+                if !((SOLID_TILES.contains(&map[xy_idx(x+1, y, width)]) && 
+                SOLID_TILES.contains(&map[xy_idx(x-1, y, width)]))
+                || (SOLID_TILES.contains(&map[xy_idx(x, y+1, width)]) && 
+                    SOLID_TILES.contains(&map[xy_idx(x, y-1, width)]))) {
+                        map[i] = TileType::Floor;
+                }
+            }
+        }
+
+        for i in 0..map.len() {
+            let x = i as i32 % width;
+            let y = i as i32 / width;
+
+            if map[i] == TileType::Wall && x > 0 && y > 0 && x < width-1 && y < height-1 &&
+                !vaults.iter().any(|room| (room.x1..=room.x2+1).contains(&x) && (room.y1..=room.y2+1).contains(&y)) &&
+                [(x+1, y), (x-1, y), (x, y+1), (x, y-1)].iter()
+                    .any(|(xx, yy)| map[xy_idx(*xx, *yy, width)] == TileType::Floor) {
+                match rng.range(1, 101) {
+                    1..=8 => map[i] = TileType::WeakWall,
+                    9..=10 => map[i] = TileType::DamagedWall,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut mechanisms: Vec<Mechanism> = vec![];
+        if rooms.len() > 3 {
+            let goals = [xy_idx(rooms[0].center().0, rooms[0].center().1, width),
+                xy_idx(rooms[1].center().0, rooms[1].center().1, width)];
+            for _i in 0..rng.range(0, 3) {
+                WorldLocation::apply_puzzle(&rooms, &mut map, &mut color, &mut mechanisms, width, &mut rng, &goals);
+            }
+        }
+
+        let mut portals: Vec<(usize, usize)> = vec![];
+        for _i in 0..rng.range(0, 3) {
+            WorldLocation::apply_portals(&rooms, &mut map, &mut color, &mut portals, width, &mut rng,
+                xy_idx(rooms[0].center().0, rooms[0].center().1, width));
+        }
+        
+        map[xy_idx(rooms[0].center().0, rooms[0].center().1, width)] = TileType::Floor;
+        color[xy_idx(rooms[1].center().0, rooms[1].center().1, width)] = WHITE;
+        map[xy_idx(rooms[1].center().0, rooms[1].center().1, width)] = if dungeon.escaping {
+            TileType::UpStair
+        } else {
+            TileType::Exit
+        };
+        if !dungeon.escaping && dungeon.current.is_none() {
+            color[xy_idx(rooms[0].center().0+1, rooms[0].center().1, width)] = WHITE;
+            map[xy_idx(rooms[0].center().0+1, rooms[0].center().1, width)] = TileType::UpStair;
+        }
+        if dungeon.is_reward() {
+            color[xy_idx(rooms[1].center().0+1, rooms[1].center().1, width)] = GOLD;
+            map[xy_idx(rooms[1].center().0+1, rooms[1].center().1, width)] = TileType::Chest;
+        }
+
+        let stairs = xy_idx(rooms[rooms.len()-1].center().0-1, rooms[rooms.len()-1].center().1, width);
+        if dungeon.has_stairs() && rooms.len() > 2 {
+            color[stairs] = MAGENTA;
+            map[stairs] = TileType::BranchStair;
+        }
+        let mut result = Self {
+            durability: map.iter().map(|tile| WorldLocation::durability_of(*tile)).collect(),
+            gas: vec![0; map.len()],
+            fire: vec![0; map.len()],
+            scent: vec![0; map.len()],
+            doorways: vec![],
+            mechanisms,
+            portals,
+            items: HashMap::new(),
+            purses: HashMap::new(),
+            occupants: vec![],
+            summons: vec![],
+            prompts: vec![],
+            sealed: None,
+            flow: Flow::default(),
+            source: map.clone(),
+            entities: entities.clone(),
+            colors: color.clone(),
+            width,
+            height,
+            start_x: rooms[0].center().0, 
+            start_y: rooms[0].center().1,
+            smash: false,
+            dirty: false,
+            alarm: false,
+            turn: 0,
+        };
+        result.index_entities();
+
+        let path = a_star_search(
+            xy_idx(rooms[0].center().0, rooms[0].center().1, width),
+            xy_idx(rooms[1].center().0, rooms[1].center().1, width),
+            &result,
+        );
+
+
+        let branch = a_star_search(xy_idx(rooms[0].center().0, rooms[0].center().1, width), stairs, &result);
+        if path.steps.len() <= 50 || path.steps.iter().any(|idx| result.source[*idx] == TileType::Lava) ||
+            (result.source[stairs] == TileType::BranchStair && !branch.success) {
+            result = WorldLocation::new(engine, width, height, exp, floor, dungeon);
+        }
+        result
+    }
+
+    pub fn is_solid(&self, (x, y): (i32, i32)) -> bool {
+        let idx = xy_idx(x, y, self.width);
+        self.occupants[idx].is_some() || SOLID_TILES.contains(&self.source[idx])
+    }
+
+    fn durability_of(tile: TileType) -> i32 {
+        match tile {
+            TileType::Wall => WALL_DURABILITY,
+            TileType::WeakWall => WEAK_WALL_DURABILITY,
+            TileType::DamagedWall => WEAK_WALL_DURABILITY/2,
+            _ => 0,
+        }
+    }
+
+    pub fn is_breakable(&self, idx: usize) -> bool {
+        self.breakable_by(idx, self.smash)
+    }
+
+    fn breakable_by(&self, idx: usize, smash: bool) -> bool {
+        let x = idx as i32 % self.width;
+        let y = idx as i32 / self.width;
+        if x <= 0 || y <= 0 || x >= self.width-1 || y >= self.height-1 {
+            return false;
+        }
+
+        match self.source[idx] {
+            TileType::WeakWall | TileType::DamagedWall => true,
+            TileType::Wall => smash,
+            _ => false,
+        }
+    }
+
+    pub fn damage_wall(&mut self, idx: usize, damage: i32) -> bool {
+        self.durability[idx] -= damage;
+        if self.durability[idx] <= 0 {
+            self.source[idx] = TileType::Rubble;
+            self.dirty = true;
+            return true;
+        } else if self.durability[idx] <= WEAK_WALL_DURABILITY/2 {
+            self.source[idx] = TileType::DamagedWall;
+        } else if self.durability[idx] <= WEAK_WALL_DURABILITY {
+            self.source[idx] = TileType::WeakWall;
+        }
+        false
+    }
+
+    pub fn tile_cost(&self, idx: usize) -> f32 {
+        match self.source[idx] {
+            TileType::ShallowWater => 1.5,
+            TileType::Rubble | TileType::Mud => 2.0,
+            TileType::Wall | TileType::WeakWall | TileType::DamagedWall | TileType::DeepWater => 3.0,
+            TileType::Lava => 20.0,
+            _ => 1.0,
+        }
+    }
+
+    pub fn is_hidden(&self, idx: usize) -> bool {
+        matches!(self.source[idx], TileType::SecretDoor | TileType::Trap(_) | TileType::TeleporterHidden)
+    }
+
+    pub fn reveal(&mut self, idx: usize) -> String {
+        match self.source[idx] {
+            TileType::SecretDoor => {
+                self.source[idx] = TileType::Door;
+                self.colors[idx] = WHITESMOKE;
+                self.dirty = true;
+                String::from("You find a secret door in the wall!")
+            }
+            TileType::Trap(trap) => {
+                self.set_trap_found(idx, trap);
+                format!("You notice a hidden {}", trap.name())
+            }
+            TileType::TeleporterHidden => {
+                self.source[idx] = TileType::Teleporter;
+                self.colors[idx] = MAGENTA;
+                String::from("You notice strange runes carved into the floor")
+            }
+            _ => String::new(),
+        }
+    }
+
+    pub fn is_locked(&self, idx: usize) -> bool {
+        matches!(self.source[idx], TileType::Door | TileType::LockedChest)
+    }
+
+    pub fn is_jammed(&self, idx: usize) -> bool {
+        self.colors[idx] != WHITESMOKE
+    }
+
+    pub fn jam(&mut self, idx: usize) {
+        self.colors[idx] = WHITE;
+    }
+
+    pub fn unlock(&mut self, idx: usize) {
+        if self.source[idx] == TileType::Door {
+            self.doorways.push(idx);
+        }
+        self.source[idx] = match self.source[idx] {
+            TileType::LockedChest => TileType::Chest,
+            _ => TileType::Floor,
+        };
+        self.dirty = true;
+    }
+
+    pub fn make_noise(&mut self, (x, y): (i32, i32), radius: i32) {
+        for entity in &mut self.entities {
+            if Pythagoras.distance2d(Point::new(x, y), Point::new(entity.x, entity.y)) <= radius as f32 {
+                entity.hear((x, y));
+            }
+        }
+    }
+
+    fn apply_room_to_map(room: &Room, map: &mut [TileType], width: i32) {
+        for y in room.y1 + 1..=room.y2 {
+            for x in room.x1 + 1..=room.x2 {
+                map[xy_idx(x, y, width)] = TileType::Floor;
+            }
+        }
+    }
+
+    fn apply_vault(room: &Room, map: &mut [TileType], colors: &mut [(u8, u8, u8)], width: i32,
+        rng: &mut RandomNumberGenerator) {
+        let wall = colors[xy_idx(room.x1, room.y1, width)];
+        for (x, y) in room.ring() {
+            let idx = xy_idx(x, y, width);
+            if map[idx] != TileType::Wall {
+                map[idx] = TileType::SecretDoor;
+                colors[idx] = wall;
+            }
+        }
+
+        let (x, y) = (rng.range(room.x1 + 1, room.x2 + 1), rng.range(room.y1 + 1, room.y2 + 1));
+        colors[xy_idx(x, y, width)] = GOLD;
+        map[xy_idx(x, y, width)] = TileType::Chest;
+    }
+
+    fn apply_terrain(room: &Room, map: &mut [TileType], colors: &mut [(u8, u8, u8)], width: i32, kind: i32,
+        rng: &mut RandomNumberGenerator) {
+        let mut set = |x: i32, y: i32, tile: TileType, color: (u8, u8, u8)| {
+            let idx = xy_idx(x, y, width);
+            if map[idx] == TileType::Floor {
+                map[idx] = tile;
+                colors[idx] = color;
+            }
+        };
+
+        let (cx, cy) = room.center();
+        match kind {
+            0 => {
+                for y in room.y1 + 2..room.y2 {
+                    for x in room.x1 + 2..room.x2 {
+                        if x > room.x1 + 2 && x < room.x2 - 1 && y > room.y1 + 2 && y < room.y2 - 1 {
+                            set(x, y, TileType::DeepWater, BLUE);
+                        } else {
+                            set(x, y, TileType::ShallowWater, DODGER_BLUE);
+                        }
+                    }
+                }
+            }
+            1 => {
+                let (mut x, mut y) = (rng.range(room.x1 + 1, room.x2 + 1), rng.range(room.y1 + 1, room.y2 + 1));
+                for _i in 0..12 {
+                    set(x, y, TileType::Mud, SADDLE_BROWN);
+                    x = (x + rng.range(-1, 2)).clamp(room.x1 + 1, room.x2);
+                    y = (y + rng.range(-1, 2)).clamp(room.y1 + 1, room.y2);
+                }
+            }
+            2 => {
+                let (x, y) = (rng.range(room.x1 + 2, room.x2), rng.range(room.y1 + 2, room.y2));
+                for (xx, yy) in [(x, y), (x+1, y), (x-1, y), (x, y+1), (x, y-1)] {
+                    set(xx, yy, TileType::Lava, ORANGE);
+                }
+            }
+            _ => {
+                let x = rng.range(room.x1 + 1, cx);
+                for y in room.y1 + 1..=room.y2 {
+                    if y == cy {
+                        set(x, y, TileType::Bridge, BURLYWOOD);
+                    } else {
+                        set(x, y, TileType::Chasm, DIM_GRAY);
+                    }
+                }
+            }
+        }
+    }
+
+    fn generate_at_room(map: &mut [TileType], colors: &mut [(u8, u8, u8)], first: (i32, i32), second: (i32, i32), 
+        width: i32, rng: &mut RandomNumberGenerator, floor: i32) {
+        let mut potion = false;
+
+        for _i in 0..(((second.1-first.1)*(second.0-first.0))/30+5) {
+            let (x, y) = (rng.range(first.0+2, second.0-2), rng.range(first.1+2, second.1-2));
+            let idx = xy_idx(x, y, width);
+
+            let chance = rng.range(1, 101);
+            if chance <= 15+floor && !potion {
+                if rng.range(1, 101) >= 85 {
+                    colors[idx] = HOT_PINK;
+                    map[idx] = TileType::Potion;
+                } else {
+                    colors[idx] = ORANGE_RED;
+                    map[idx] = TileType::Potion;
+                }
+                potion = true;
+            } else if chance == 16+floor {
+                colors[idx] = GOLD;
+                map[idx] = TileType::Coin;
+            } else if chance >= 98 {
+                colors[idx] = WHITESMOKE;
+                if rng.range(1, 4) == 1 {
+                    map[idx] = TileType::LockedChest;
+                } else {
+                    map[idx] = TileType::Chest;
+                }
+            }
+        }
+    }
+
+    fn generate_tunnel(map: &mut [TileType], colors: &mut [(u8, u8, u8)], width: i32, height: i32, x: i32, y: i32,
+        rng: &mut RandomNumberGenerator, ok: bool) -> bool {
+        let idx = xy_idx(x, y, width);
+        if idx > 0 && idx < (width * height) as usize {
+            let chance = rng.range(1, 101);
+            if chance <= 2 && !ok {
+                map[idx] = TileType::Trap(TrapType::random(rng));
+                colors[idx] = WHITESMOKE;
+                return true;
+            } else if chance <= 7 && (
+                (SOLID_TILES.contains(&map[xy_idx(x+1, y, width)]) && 
+                SOLID_TILES.contains(&map[xy_idx(x-1, y, width)]))
+                || (SOLID_TILES.contains(&map[xy_idx(x, y+1, width)]) && 
+                    SOLID_TILES.contains(&map[xy_idx(x, y-1, width)]))) {
+                if chance == 3 {
+                    map[idx] = TileType::SecretDoor;
+                } else {
+                    colors[idx] = WHITESMOKE;
+                    map[idx] = TileType::Door;
+                }
+            } else {
+                map[idx as usize] = TileType::Floor;
+            }
+        }
+        false
+    }
+
+    fn apply_horizontal_tunnel(map: &mut [TileType], colors: &mut [(u8, u8, u8)], x1: i32, x2: i32, y: i32, 
+        width: i32, height: i32, rng: &mut RandomNumberGenerator) {
+        let mut have = false;
+        for x in min(x1, x2)..=max(x1, x2) {
+            have = WorldLocation::generate_tunnel(map, colors, 
+                width, height, x, y, rng, have)
+        }
+    }
+
+    fn apply_vertical_tunnel(map: &mut [TileType], colors: &mut [(u8, u8, u8)], y1: i32, y2: i32, x: i32, 
+        width: i32, height: i32, rng: &mut RandomNumberGenerator) {
+        let mut have = false;
+        for y in min(y1, y2)..=max(y1, y2) {
+            have = WorldLocation::generate_tunnel(map, colors, 
+                width, height, x, y, rng, have)
+        }
+    }
+
+    fn is_exit_valid(&self, x: i32, y: i32, smash: bool, crowded: bool) -> bool {
+        if !(1..=self.width - 1).contains(&x) || !(1..=self.height - 1).contains(&y) {
+            return false;
+        }
+        if smash && self.breakable_by(xy_idx(x, y, self.width), true) {
+            return true;
+        }
+        if self.source[xy_idx(x, y, self.width)] == TileType::Chasm {
+            return false;
+        }
+        let solid = if crowded {
+            self.is_solid((x, y))
+        } else {
+            SOLID_TILES.contains(&self.source[xy_idx(x, y, self.width)])
+        };
+        !solid && !matches!(self.source[xy_idx(x, y, self.width)], TileType::TrapFound(_))
+    }
+
+    pub fn exits(&self, idx: usize, smash: bool, crowded: bool) -> SmallVec<[(usize, f32); 10]> {
+        let mut exits = SmallVec::new();
+        let x = idx as i32 % self.width;
+        let y = idx as i32 / self.width;
+        let w = self.width as usize;
+
+        if self.is_exit_valid(x-1, y, smash, crowded) { exits.push((idx-1, self.tile_cost(idx-1))) };
+        if self.is_exit_valid(x+1, y, smash, crowded) { exits.push((idx+1, self.tile_cost(idx+1))) };
+        if self.is_exit_valid(x, y-1, smash, crowded) { exits.push((idx-w, self.tile_cost(idx-w))) };
+        if self.is_exit_valid(x, y+1, smash, crowded) { exits.push((idx+w, self.tile_cost(idx+w))) };
+        if let Some(dest) = self.portal_of(idx) {
+            if self.is_exit_valid(dest as i32 % self.width, dest as i32 / self.width, smash, crowded) { exits.push((dest, 1.0)) };
+        }
+
+        exits
+    }
+
+    pub fn draw(&mut self, player: &mut Player, engine: &mut BTerm) -> Vec<String> {
+        if self.alarm {
+            for entity in &mut self.entities {
+                entity.alert = 30;
+            }
+            self.alarm = false;
+        }
+
+        let mut messages = self.update_fields(player);
+        messages.append(&mut self.update_mechanisms(player));
+        if self.dirty {
+            player.update_fov(self);
+            self.dirty = false;
+        }
+
+        for tile in &player.fov.far_tiles() {
+            let symbol = match self.source[xy_idx(tile.x, tile.y, self.width)] {
+                TileType::Potion => '!',
+                TileType::Floor | TileType::Trap(_) | TileType::TeleporterHidden => ' ',
+                TileType::Wall | TileType::SecretDoor => '#',
+                TileType::Exit | TileType::SealedExit => '<',
+                TileType::BranchStair => '>',
+                TileType::UpStair => '↑',
+                TileType::Artifact => '♦',
+                TileType::Coin => '$',
+                TileType::BearTrapActived | TileType::TrapFound(_) | TileType::TrapSet(_) => '^',
+                TileType::Item => self.item_symbol(xy_idx(tile.x, tile.y, self.width)),
+                TileType::BloodStain | TileType::Ash => '.',
+                TileType::Door => '+',
+                TileType::WeakWall => 'W',
+                TileType::DamagedWall => 'M',
+                TileType::Rubble => ',',
+                TileType::DeepWater => '≈',
+                TileType::ShallowWater | TileType::Mud | TileType::Lava => '~',
+                TileType::Chasm => '░',
+                TileType::Bridge => '=',
+                TileType::Lever => '/',
+                TileType::LeverPulled => '\\',
+                TileType::PressurePlate => '_',
+                TileType::Gate => '≡',
+                TileType::GateOpen => '∩',
+                TileType::Boulder => 'O',
+                TileType::Teleporter => 'Ω',
+                TileType::Tree1 => '♣',
+                TileType::Tree2 => '♠',
+                TileType::Campfire => '☼',
+                TileType::Chest | TileType::LockedChest => '*',
+            };
+
+            let idx = xy_idx(tile.x, tile.y, self.width);
+            let (symbol, color) = if self.fire[idx] > 0 {
+                ('▲', ORANGE)
+            } else if self.gas[idx] >= GAS_THICK {
+                ('▒', CHARTREUSE)
+            } else if self.gas[idx] >= GAS_HARM && symbol == ' ' {
+                ('░', CHARTREUSE)
+            } else {
+                (symbol, self.colors[idx])
+            };
+
+            if player.pos != (tile.x, tile.y) {    
+                if !player.sees((tile.x, tile.y)) {
+                    if matches!(symbol, '#' | 'W' | 'M') {
+                        State::put(engine, player.pos, tile.x, tile.y, 
+                            GRAY, symbol);
+                    }
+                } else {
+                    if symbol != '@' {
+                        State::put(engine, player.pos, tile.x, tile.y, 
+                            color, symbol);
+                    } else {
+                        State::put_red(engine, player.pos, tile.x, tile.y, 
+                            color, symbol);
+                    }
+                } 
+            }
+        }
+
+        self.update_flow(player);
+        self.turn += 1;
+        for i in 0..self.entities.len() {
+            let (x, y) = (self.entities[i].x, self.entities[i].y);
+            if self.entities[i].should_act(player, self.turn + i) {
+                let mut entity = std::mem::take(&mut self.entities[i]);
+                let event = entity.update(player, self, engine);
+                self.relocate(i, (x, y), &entity);
+                self.entities[i] = entity;
+                if !event.is_empty() {
+                    messages.push(event);
+                }
+            } else if player.sees((x, y)) {
+                self.entities[i].draw(engine, player.pos);
+            }
+        }
+        if !self.summons.is_empty() {
+            let mut summons = std::mem::take(&mut self.summons);
+            self.entities.append(&mut summons);
+            self.index_entities();
+        }
+        for entity in &self.entities {
+            for idx in &entity.charge {
+                let (x, y) = (*idx as i32 % self.width, *idx as i32 / self.width);
+                if player.sees((x, y)) && player.pos != (x, y) {
+                    State::put_red(engine, player.pos, x, y, YELLOW3, '!');
+                }
+            }
+        }
+        if let Some((idx, tile)) = self.sealed {
+            if self.boss().is_none() {
+                self.source[idx] = tile;
+                self.sealed = None;
+                if tile == TileType::Artifact {
+                    self.colors[idx] = GOLD;
+                    messages.push(String::from("With the boss dead, the grate lifts and the legendary treasure glitters behind it"));
+                } else {
+                    self.colors[idx] = WHITE;
+                    messages.push(String::from("With the boss dead, the stairs down rumble open"));
+                }
+            }
+        }
+        messages.append(&mut self.drop_carried(player));
+        messages
+    }
+}
+
+impl BaseMap for WorldLocation {
+    fn is_opaque(&self, idx: usize) -> bool {
+        !(TRANSPARENT_TILES.contains(&self.source[idx]) || self.source[idx] == TileType::Door ||
+            matches!(self.source[idx], TileType::Trap(_) | TileType::TrapFound(_) | TileType::TrapSet(_)))
+            || self.gas[idx] >= GAS_THICK
+    }
+    fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
+        let w = self.width as usize;
+        let p1 = Point::new(idx1 % w, idx1 / w);
+        let p2 = Point::new(idx2 % w, idx2 / w);
+        Pythagoras.distance2d(p1, p2)
+    }
+    fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
+        self.exits(idx, self.smash, true)
+    }
+}
+
+impl Algorithm2D for WorldLocation {
+    fn dimensions(&self) -> Point {
+        Point::new(self.width, self.height)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Room {
+    pub x1: i32,
+    pub x2: i32,
+    pub y1: i32,
+    pub y2: i32,
+}
+
+impl Room {
+    pub fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+        Self {
+            x1: x,
+            y1: y,
+            x2: x + w,
+            y2: y + h,
+        }
+    }
+
+    pub fn intersect(&self, other: &Self) -> bool {
+        self.x1 <= other.x2 && self.x2 >= other.x1 && self.y1 <= other.y2 && self.y2 >= other.y1
+    }
+
+    pub fn ring(&self) -> Vec<(i32, i32)> {
+        let mut ring = vec![];
+        for x in self.x1..=self.x2 + 1 {
+            ring.push((x, self.y1));
+            ring.push((x, self.y2 + 1));
+        }
+        for y in self.y1 + 1..=self.y2 {
+            ring.push((self.x1, y));
+            ring.push((self.x2 + 1, y));
+        }
+        ring
+    }
+
+    pub fn center(&self) -> (i32, i32) {
+        ((self.x1 + self.x2) / 2, (self.y1 + self.y2) / 2)
+    }
+}
@@ -0,0 +1,47 @@
+use crate::entity::Entity;
+use super::{xy_idx, WorldLocation};
+
+impl WorldLocation {
+    pub fn index_entities(&mut self) {
+        self.occupants = vec![None; self.source.len()];
+        for (i, entity) in self.entities.iter().enumerate() {
+            if entity.health > 0 {
+                self.occupants[xy_idx(entity.x, entity.y, self.width)] = Some(i);
+            }
+        }
+    }
+
+    pub fn occupant(&self, (x, y): (i32, i32)) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None
+        }
+        self.occupants[xy_idx(x, y, self.width)]
+    }
+
+    pub fn relocate(&mut self, i: usize, (x, y): (i32, i32), entity: &Entity) {
+        let from = xy_idx(x, y, self.width);
+        if self.occupants[from] == Some(i) {
+            self.occupants[from] = None;
+        }
+        if entity.health > 0 {
+            self.occupants[xy_idx(entity.x, entity.y, self.width)] = Some(i);
+        }
+    }
+
+    pub fn swap(&mut self, j: usize, (x, y): (i32, i32)) {
+        let (xx, yy) = (self.entities[j].x, self.entities[j].y);
+        self.occupants[xy_idx(xx, yy, self.width)] = None;
+        self.occupants[xy_idx(x, y, self.width)] = Some(j);
+        self.entities[j].x = x;
+        self.entities[j].y = y;
+    }
+
+    pub fn summon(&mut self, entity: Entity) {
+        self.occupants[xy_idx(entity.x, entity.y, self.width)] = Some(self.entities.len() + self.summons.len());
+        self.summons.push(entity);
+    }
+
+    pub fn vacate(&mut self, (x, y): (i32, i32)) {
+        self.occupants[xy_idx(x, y, self.width)] = None;
+    }
+}
@@ -0,0 +1,76 @@
+use bracket_lib::{color::MAGENTA, pathfinding::DijkstraMap, random::RandomNumberGenerator};
+
+use super::{xy_idx, Room, TileType, WorldLocation};
+
+impl WorldLocation {
+    pub fn portal_of(&self, idx: usize) -> Option<usize> {
+        self.portals.iter().find_map(|(a, b)| {
+            if *a == idx {
+                Some(*b)
+            } else if *b == idx {
+                Some(*a)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn describe_portal(&self, idx: usize) -> Option<String> {
+        let dest = self.portal_of(idx)?;
+        let (dx, dy) = (dest as i32 % self.width - idx as i32 % self.width,
+            dest as i32 / self.width - idx as i32 / self.width);
+        let direction = match (dy.signum(), dx.signum()) {
+            (-1, -1) => "north-west",
+            (-1, 0) => "north",
+            (-1, _) => "north-east",
+            (0, -1) => "west",
+            (0, _) => "east",
+            (_, -1) => "south-west",
+            (_, 0) => "south",
+            _ => "south-east",
+        };
+        Some(format!("{} steps to the {}", dx.abs().max(dy.abs()), direction))
+    }
+
+    pub fn random_reachable(&self, from: usize) -> Option<usize> {
+        let dijkstra = DijkstraMap::new(self.width, self.height, &[from], self, 1000.0);
+        let tiles: Vec<usize> = (0..self.source.len())
+            .filter(|idx| *idx != from && self.source[*idx] == TileType::Floor && dijkstra.map[*idx] < f32::MAX)
+            .collect();
+
+        if tiles.is_empty() {
+            return None;
+        }
+        Some(tiles[RandomNumberGenerator::new().range(0, tiles.len())])
+    }
+
+    pub fn apply_portals(rooms: &[Room], map: &mut [TileType], colors: &mut [(u8, u8, u8)],
+        portals: &mut Vec<(usize, usize)>, width: i32, rng: &mut RandomNumberGenerator, start: usize) {
+        let mut pads = vec![];
+        for room in [rng.range(0, rooms.len()), rng.range(0, rooms.len())] {
+            let room = rooms[room];
+            let (x, y) = (rng.range(room.x1 + 1, room.x2 + 1), rng.range(room.y1 + 1, room.y2 + 1));
+            let idx = xy_idx(x, y, width);
+            let landing = rooms[..2].iter().any(|room| (room.center().0 - x).abs() <= 1 && (room.center().1 - y).abs() <= 1);
+            if map[idx] != TileType::Floor || pads.contains(&idx) || landing {
+                return;
+            }
+            pads.push(idx);
+        }
+
+        if !WorldLocation::is_solvable(map, &[], width, start, &pads) {
+            return;
+        }
+
+        let hidden = rng.range(1, 4) == 1;
+        for idx in &pads {
+            if hidden {
+                map[*idx] = TileType::TeleporterHidden;
+            } else {
+                map[*idx] = TileType::Teleporter;
+                colors[*idx] = MAGENTA;
+            }
+        }
+        portals.push((pads[0], pads[1]));
+    }
+}
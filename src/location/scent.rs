@@ -0,0 +1,35 @@
+use bracket_lib::pathfinding::BaseMap;
+
+use crate::player::Player;
+use super::{xy_idx, TileType, WorldLocation};
+
+const SCENT_TRAIL: u8 = 60;
+const SCENT_BLOOD: u8 = 200;
+
+impl WorldLocation {
+    fn holds_scent(&self, idx: usize) -> bool {
+        !matches!(self.source[idx], TileType::DeepWater | TileType::ShallowWater)
+    }
+
+    pub fn update_scent(&mut self, player: &Player) {
+        for idx in 0..self.scent.len() {
+            self.scent[idx] = if self.holds_scent(idx) { self.scent[idx].saturating_sub(1) } else { 0 };
+        }
+        for idx in &self.doorways {
+            self.scent[*idx] = 0;
+        }
+
+        let idx = xy_idx(player.pos.0, player.pos.1, self.width);
+        if self.holds_scent(idx) && !self.doorways.contains(&idx) {
+            let strength = if player.blood > 0 { SCENT_BLOOD } else { SCENT_TRAIL };
+            self.scent[idx] = self.scent[idx].max(strength);
+        }
+    }
+
+    pub fn sniff(&self, idx: usize) -> Option<usize> {
+        self.get_available_exits(idx).iter()
+            .map(|(next, _)| *next)
+            .filter(|next| self.scent[*next] > self.scent[idx])
+            .max_by_key(|next| self.scent[*next])
+    }
+}
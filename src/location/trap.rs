@@ -0,0 +1,146 @@
+use bracket_lib::{color::{CHARTREUSE, DIM_GRAY, GOLD, LIGHT_GREEN, MAGENTA, SANDY_BROWN, YELLOW}, random::RandomNumberGenerator};
+
+use crate::entity::Entity;
+use super::{xy_idx, TileType, WorldLocation};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TrapType {
+    Bear,
+    Dart,
+    Alarm,
+    Pit,
+    Teleport,
+    Gas,
+    Ceiling,
+}
+
+impl TrapType {
+    pub fn random(rng: &mut RandomNumberGenerator) -> Self {
+        match rng.range(1, 101) {
+            1..=30 => TrapType::Bear,
+            31..=45 => TrapType::Dart,
+            46..=60 => TrapType::Gas,
+            61..=70 => TrapType::Alarm,
+            71..=80 => TrapType::Teleport,
+            81..=90 => TrapType::Pit,
+            _ => TrapType::Ceiling,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            TrapType::Bear => "bear trap",
+            TrapType::Dart => "poison dart trap",
+            TrapType::Alarm => "alarm tripwire",
+            TrapType::Pit => "covered pit",
+            TrapType::Teleport => "teleport glyph",
+            TrapType::Gas => "gas vent",
+            TrapType::Ceiling => "loose ceiling",
+        }
+    }
+
+    pub fn color(&self) -> (u8, u8, u8) {
+        match self {
+            TrapType::Bear => YELLOW,
+            TrapType::Dart => LIGHT_GREEN,
+            TrapType::Alarm => GOLD,
+            TrapType::Pit => DIM_GRAY,
+            TrapType::Teleport => MAGENTA,
+            TrapType::Gas => CHARTREUSE,
+            TrapType::Ceiling => SANDY_BROWN,
+        }
+    }
+
+    pub fn is_portable(&self) -> bool {
+        !matches!(self, TrapType::Pit | TrapType::Ceiling)
+    }
+
+    pub fn detection(&self) -> i32 {
+        match self {
+            TrapType::Bear | TrapType::Gas => 0,
+            TrapType::Dart | TrapType::Alarm => 10,
+            TrapType::Teleport => 15,
+            TrapType::Pit => 20,
+            TrapType::Ceiling => 25,
+        }
+    }
+
+    pub fn disarm(&self) -> i32 {
+        match self {
+            TrapType::Alarm => 60,
+            TrapType::Dart => 50,
+            TrapType::Bear => 40,
+            TrapType::Gas | TrapType::Ceiling => 30,
+            TrapType::Pit | TrapType::Teleport => 20,
+        }
+    }
+}
+
+impl WorldLocation {
+    pub fn trap_at(&self, idx: usize) -> Option<TrapType> {
+        match self.source[idx] {
+            TileType::Trap(trap) | TileType::TrapFound(trap) | TileType::TrapSet(trap) => Some(trap),
+            _ => None,
+        }
+    }
+
+    pub fn hide_difficulty(&self, idx: usize) -> i32 {
+        match self.source[idx] {
+            TileType::Trap(trap) => trap.detection(),
+            _ => 0,
+        }
+    }
+
+    pub fn set_trap_found(&mut self, idx: usize, trap: TrapType) {
+        self.source[idx] = TileType::TrapFound(trap);
+        self.colors[idx] = trap.color();
+    }
+
+    pub fn trigger_trap(&mut self, entity: &mut Entity, player: (i32, i32)) -> String {
+        let idx = xy_idx(entity.x, entity.y, self.width);
+        let trap = match self.trap_at(idx) {
+            Some(trap) => trap,
+            None => return String::new(),
+        };
+        self.set_trap_found(idx, trap);
+
+        let name = entity.name();
+        match trap {
+            TrapType::Bear => {
+                self.source[idx] = TileType::BearTrapActived;
+                entity.health -= 6;
+                format!("{} steps into a bear trap", name)
+            }
+            TrapType::Dart => {
+                entity.health -= 4;
+                format!("{} is hit by a poison dart", name)
+            }
+            TrapType::Alarm => {
+                self.alarm = true;
+                format!("{} trips an alarm, a bell rings out!", name)
+            }
+            TrapType::Pit => {
+                entity.health = 0;
+                format!("{} falls into a pit", name)
+            }
+            TrapType::Teleport => {
+                let avoid = xy_idx(player.0, player.1, self.width);
+                if let Some(dest) = self.random_reachable(idx).filter(|dest| *dest != avoid) {
+                    entity.x = dest as i32 % self.width;
+                    entity.y = dest as i32 / self.width;
+                }
+                format!("{} vanishes in a flash", name)
+            }
+            TrapType::Gas => {
+                self.release_gas((entity.x, entity.y));
+                self.source[idx] = TileType::Floor;
+                format!("{} sets off a gas vent", name)
+            }
+            TrapType::Ceiling => {
+                self.source[idx] = TileType::Rubble;
+                entity.health -= 10;
+                format!("The ceiling collapses on {}", name)
+            }
+        }
+    }
+}
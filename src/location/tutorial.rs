@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use bracket_lib::{color::{GOLD, GRAY, GREY100, ORANGE_RED, RGB, WHITE, WHITESMOKE}, terminal::BTerm};
+
+use crate::{entity::{Entity, EntityType}, player::{ItemType, Player}};
+use super::{flow::Flow, trap::TrapType, xy_idx, TileType, WorldLocation};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PromptTrigger {
+    Step(usize),
+    Potion,
+    Hurt,
+    Kill,
+}
+
+const TRAINING_GROUNDS: [&str; 9] = [
+    "##############################################",
+    "#.....#.........#........#..........#........#",
+    "#.....#....*....#...$....#..........#........#",
+    "#.....#.........#........#..........#........#",
+    "#.@..1D2........3.......4T5.....s...6....<...#",
+    "#.....W.........#........#..........#........#",
+    "#.....#....!....#........#..........#........#",
+    "#.....#.........#........#..........#........#",
+    "##############################################",
+];
+
+const STEPS: [&str; 7] = [
+    "Welcome to the training grounds! Move with WASD or the arrow keys\nLeft click on any tile you can see to inspect it",
+    "Walk into the door to pick its lock, or to force it if you have no lockpicks\nIf the lock jams, walk into the cracked wall W below it until it breaks",
+    "Step onto the * chest to open it and onto the ! potion to pick it up",
+    "Coins $ can be spent at the traders on the surface",
+    "The ^ ahead is a bear trap. Stand next to it and press X to disarm it\nPress F to search for traps you haven't noticed yet",
+    "A skeleton guards this room. Walk into a monster to attack it",
+    "The < is a ladder. Step on it to leave for the surface camp",
+];
+
+impl WorldLocation {
+    pub fn tutorial(engine: &mut BTerm, width: i32, height: i32) -> Self {
+        engine.screen_burn_color(RGB::from_u8(GREY100.0, GREY100.1, GREY100.2));
+
+        let mut map = vec![TileType::Wall; (width * height) as usize];
+        let mut colors = vec![GREY100; (width * height) as usize];
+        let mut entities = vec![];
+        let mut prompts = vec![];
+        let mut start = (0, 0);
+
+        let left = (width - TRAINING_GROUNDS[0].len() as i32) / 2;
+        let top = (height - TRAINING_GROUNDS.len() as i32) / 2;
+        for (y, row) in TRAINING_GROUNDS.iter().enumerate() {
+            for (x, symbol) in row.chars().enumerate() {
+                let (x, y) = (left + x as i32, top + y as i32);
+                let idx = xy_idx(x, y, width);
+                let (tile, color) = match symbol {
+                    '#' => (TileType::Wall, GREY100),
+                    'W' => (TileType::WeakWall, GREY100),
+                    'D' => (TileType::Door, WHITESMOKE),
+                    '*' => (TileType::Chest, WHITESMOKE),
+                    '!' => (TileType::Potion, ORANGE_RED),
+                    '$' => (TileType::Coin, GOLD),
+                    'T' => (TileType::TrapFound(TrapType::Bear), TrapType::Bear.color()),
+                    '<' => (TileType::Exit, WHITE),
+                    _ => (TileType::Floor, GRAY),
+                };
+                map[idx] = tile;
+                colors[idx] = color;
+
+                match symbol {
+                    '@' => {
+                        start = (x, y);
+                        prompts.push((PromptTrigger::Step(idx), STEPS[0]));
+                    }
+                    's' => entities.push(Entity::new(x, y, EntityType::Skeleton, 0)),
+                    '1'..='6' => prompts.push((PromptTrigger::Step(idx), STEPS[symbol as usize - '0' as usize])),
+                    _ => {}
+                }
+            }
+        }
+        prompts.push((PromptTrigger::Potion, "Press I to open the inventory and the item's letter to use it\nShift+letter drops it, dropped items make noise and lure monsters"));
+        prompts.push((PromptTrigger::Hurt, "You are hurt! Drink a red potion from your inventory to heal"));
+        prompts.push((PromptTrigger::Kill, "The skeleton is dead. Press Tab at any time to see the dungeon overview"));
+
+        let mut result = Self {
+            durability: map.iter().map(|tile| WorldLocation::durability_of(*tile)).collect(),
+            gas: vec![0; map.len()],
+            fire: vec![0; map.len()],
+            scent: vec![0; map.len()],
+            doorways: vec![],
+            mechanisms: vec![],
+            portals: vec![],
+            items: HashMap::new(),
+            purses: HashMap::new(),
+            occupants: vec![],
+            summons: vec![],
+            prompts,
+            sealed: None,
+            flow: Flow::default(),
+            source: map,
+            entities,
+            colors,
+            width,
+            height,
+            start_x: start.0,
+            start_y: start.1,
+            smash: false,
+            dirty: false,
+            alarm: false,
+            turn: 0,
+        };
+        result.index_entities();
+        result
+    }
+
+    pub fn prompts(&mut self, player: &Player) -> Vec<String> {
+        let mut messages = vec![];
+        let mut i = 0;
+        while i < self.prompts.len() {
+            let fired = match self.prompts[i].0 {
+                PromptTrigger::Step(idx) => (idx as i32 % self.width - player.pos.0).abs() <= 1 &&
+                    (idx as i32 / self.width - player.pos.1).abs() <= 1,
+                PromptTrigger::Potion => player.inventory.iter().any(|item| matches!(item, ItemType::Potion(_, _))),
+                PromptTrigger::Hurt => player.health < player.max_health,
+                PromptTrigger::Kill => self.entities.iter().all(|e| e.health <= 0 && !e.is_bones()),
+            };
+
+            if fired {
+                messages.push(String::from(self.prompts.remove(i).1));
+            } else {
+                i += 1;
+            }
+        }
+        messages
+    }
+}
@@ -1,12 +1,12 @@
-use bracket_lib::{color::{BLACK, CYAN, GREEN, PURPLE, RED, RGB, WHITESMOKE, YELLOWGREEN}, terminal::{letter_to_option, main_loop, to_cp437, BTerm, BTermBuilder, GameState, Point, VirtualKeyCode}};
+use bracket_lib::{random::RandomNumberGenerator, color::{BLACK, CYAN, GOLD, GREEN, PURPLE, RED, RGB, WHITESMOKE, YELLOWGREEN}, terminal::{letter_to_option, main_loop, to_cp437, BTerm, BTermBuilder, GameState, VirtualKeyCode}};
 use chrono::Utc;
-use std::{fs, process, time::Instant};
+use std::{collections::HashMap, fs, process, time::Instant};
 
 pub mod player;
 pub mod location;
 pub mod entity;
 
-use location::{WorldLocation, TileType, xy_idx};
+use location::{branch::{Dungeon, FINAL_DEPTH}, WorldLocation, TileType, xy_idx};
 use player::{
     gui::{self, draw_ui, BOTTOM_TABLE_HEIGHT, RIGHT_TABLE_WIDTH},
     Player,
@@ -14,6 +14,7 @@ use player::{
 
 const REAL_WIDTH: i32 = 80;
 const REAL_HEIGHT: i32 = 45;
+const FLOOR_SIZE: i32 = 50;
 
 #[derive(Clone)]
 struct State {
@@ -34,6 +35,13 @@ struct State {
     dir: i32,
     inventory: bool, 
     wheel: i32,
+    minigame: bool,
+    dungeon: Dungeon,
+    overview: bool,
+    campaign: bool,
+    tutorial: bool,
+    victory: bool,
+    arenas: HashMap<i32, WorldLocation>,
 }
 
 impl GameState for State {
@@ -75,20 +83,84 @@ impl GameState for State {
                 engine.print_color_centered(18, CYAN, BLACK, format!("Intelligence: {}/5", self.intelligence));
                 engine.print_color_centered(19, PURPLE, BLACK, format!("=> Agility: {}/5", self.agility));
             }
+
+            if self.minigame {
+                engine.print_color_centered(21, WHITESMOKE, BLACK, "Lockpicking: minigame (press M to switch)");
+            } else {
+                engine.print_color_centered(21, WHITESMOKE, BLACK, "Lockpicking: simple roll (press M to switch)");
+            }
+
+            if self.campaign {
+                engine.print_color_centered(22, WHITESMOKE, BLACK, 
+                    format!("Mode: campaign, the treasure lies on floor {} (press C to switch)", FINAL_DEPTH));
+            } else {
+                engine.print_color_centered(22, WHITESMOKE, BLACK, "Mode: endless (press C to switch)");
+            }
+
+            if self.tutorial {
+                engine.print_color_centered(23, WHITESMOKE, BLACK, "Tutorial: on (press T to switch)");
+            } else {
+                engine.print_color_centered(23, WHITESMOKE, BLACK, "Tutorial: off (press T to switch)");
+            }
         } else {
-            if self.player.health <= 0 {
+            if self.victory {
+                self.victory_screen(engine);
+            } else if self.player.health <= 0 {
                 self.game_over(engine);
             } else {
                 if !input.1.is_empty() {
-                    let ss = input.1.clone();
                     self.messages.push(input.1);
-                    
-                    if ss == String::from("You go down to the floor below...") {
-                        self.root_map = WorldLocation::new(engine, self.root_map.width*3/2, 
-                            self.root_map.height*3/2, self.player.exp, self.floor);
-                        self.player.pos = (self.root_map.start_x, self.root_map.start_y);
-                        self.restart(engine);
-                        self.floor += 1;
+                }
+                if self.player.descend {
+                    self.player.descend = false;
+                    self.dungeon.escaping |= self.player.artifact;
+                    let cleared = self.cleared_arena();
+                    let surface = self.dungeon.is_surface();
+                    let message = if self.tutorial {
+                        self.tutorial = false;
+                        String::from("You leave the training grounds and head for the surface camp")
+                    } else {
+                        self.dungeon.descend(self.player.branch)
+                    };
+                    self.player.branch = false;
+
+                    let (width, height) = if surface {
+                        (FLOOR_SIZE, FLOOR_SIZE)
+                    } else if self.dungeon.current.is_none() {
+                        (self.root_map.width*3/2, self.root_map.height*3/2)
+                    } else {
+                        (self.root_map.width, self.root_map.height)
+                    };
+                    let floor = if self.dungeon.escaping { self.dungeon.difficulty() } else { self.floor };
+                    self.load_floor(engine, width, height, floor, cleared);
+                    self.player.pos = (self.root_map.start_x, self.root_map.start_y);
+                    self.restart(engine);
+                    self.floor = self.dungeon.depth();
+                    if !message.is_empty() {
+                        self.messages.push(message);
+                    }
+                    let feeling = self.root_map.feeling(&self.dungeon, &self.player);
+                    if !feeling.is_empty() {
+                        self.messages.push(feeling);
+                    }
+                }
+                if self.player.ascend {
+                    self.player.ascend = false;
+                    self.dungeon.escaping |= self.player.artifact;
+                    let cleared = self.cleared_arena();
+                    if self.dungeon.ascend() && self.player.artifact {
+                        self.victory = true;
+                        return;
+                    }
+
+                    self.load_floor(engine, self.root_map.width*2/3, self.root_map.height*2/3, 
+                        self.dungeon.difficulty(), cleared);
+                    self.player.pos = (self.root_map.start_x, self.root_map.start_y);
+                    self.restart(engine);
+                    self.floor = self.dungeon.depth();
+                    let feeling = self.root_map.feeling(&self.dungeon, &self.player);
+                    if !feeling.is_empty() {
+                        self.messages.push(feeling);
                     }
                 }
                 if input.0 {
@@ -102,12 +174,14 @@ impl GameState for State {
                     if !messages.is_empty() {
                         self.messages.append(&mut messages);
                     }
+                    self.messages.append(&mut self.root_map.prompts(&self.player));
                         
                     println!("The move is made in {} seconds", timer.elapsed().as_secs_f32());
                 }
 
                 self.what_is_it(engine);
-                draw_ui(&self.messages, self.player.clone(), engine);
+                draw_ui(&self.messages, self.player.clone(), self.root_map.boss(), engine);
+                gui::draw_lock(engine, &self.player);
             }
         }
     }
@@ -116,7 +190,8 @@ impl GameState for State {
 impl State {
     pub fn new(engine: &mut BTerm) -> Self {
         fs::create_dir_all("./screenshots").unwrap();
-        let map = WorldLocation::new(engine, 50, 50, 0, 1);
+        let dungeon = Dungeon::new(&mut RandomNumberGenerator::new());
+        let map = WorldLocation::new(engine, FLOOR_SIZE, FLOOR_SIZE, 0, 1, &dungeon);
         Self {
             root_map: map.clone(),
             game_start: false,
@@ -135,6 +210,13 @@ impl State {
             dir: 0,
             wheel: 0,
             inventory: false,
+            minigame: true,
+            dungeon,
+            overview: false,
+            campaign: true,
+            tutorial: false,
+            victory: false,
+            arenas: HashMap::new(),
         }
     }
 
@@ -179,15 +261,33 @@ impl State {
                     }
                     self.inventory = !self.inventory;
                 },
+                VirtualKeyCode::Tab if self.game_start => {
+                    if !self.overview && self.player.health >= 0 {
+                        gui::draw_overview(engine, &self.dungeon)
+                    } else {
+                        self.restart(engine);
+                    }
+                    self.overview = !self.overview;
+                },
+                _ if self.overview => {},
                 VirtualKeyCode::F2 => engine.screenshot(format!("screenshots/screen{}.png", Utc::now().timestamp())),
                 _ => { 
-                    if self.player.health >= 0 && self.game_start && !self.inventory {
+                    if self.player.health >= 0 && self.game_start && self.player.lock.is_some() {
+                        ok = matches!(key, VirtualKeyCode::Space | VirtualKeyCode::Return | VirtualKeyCode::Back);
+                        str = self.player.pick_lock(key, &mut self.root_map);
+                    } else if self.player.health >= 0 && self.game_start && self.player.placing.is_some() && !self.inventory {
+                        str = self.player.place_trap(key, &mut self.root_map);
+                        ok = self.player.placing.is_none();
+                    } else if self.player.health >= 0 && self.game_start && !self.inventory {
                         ok = true;
                         str = self.player.action(key, &mut self.root_map);
                     } else {
                         match key {
                             VirtualKeyCode::Space => {
                                 if !self.game_start {
+                                    if self.tutorial {
+                                        self.root_map = WorldLocation::tutorial(engine, FLOOR_SIZE, FLOOR_SIZE);
+                                    }
                                     self.player = Player::new(
                                         self.root_map.start_x, 
                                         self.root_map.start_y, 
@@ -196,10 +296,22 @@ impl State {
                                         self.strength, 
                                         self.intelligence, 
                                         self.agility);
+                                    self.player.minigame = self.minigame;
+                                    self.dungeon.final_depth = if self.campaign { Some(FINAL_DEPTH) } else { None };
                                     self.game_start = true;
+                                    self.messages.append(&mut self.root_map.prompts(&self.player));
                                     self.restart(engine);
                                 }
                             }
+                            VirtualKeyCode::M if !self.game_start => {
+                                self.minigame = !self.minigame;
+                            }
+                            VirtualKeyCode::C if !self.game_start => {
+                                self.campaign = !self.campaign;
+                            }
+                            VirtualKeyCode::T if !self.game_start => {
+                                self.tutorial = !self.tutorial;
+                            }
                             VirtualKeyCode::Up => {
                                 self.dir -= 1;
                                 if self.dir < 0 {
@@ -282,7 +394,11 @@ impl State {
                                 if j > 8 {
                                     j -= 1;
                                 }
-                                let s = self.player.use_inventory(j); 
+                                let s = if engine.shift {
+                                    self.player.drop_item(j, &mut self.root_map)
+                                } else {
+                                    self.player.use_inventory(j)
+                                };
                                 gui::draw_inventory(engine, &mut self.player, self.wheel);
                                 s
                             }
@@ -295,12 +411,31 @@ impl State {
         (ok, str)
     }
 
+    fn cleared_arena(&self) -> Option<i32> {
+        (self.dungeon.is_boss() && self.root_map.boss().is_none()).then_some(self.dungeon.main)
+    }
+
+    fn load_floor(&mut self, engine: &mut BTerm, width: i32, height: i32, floor: i32, cleared: Option<i32>) {
+        let arena = if self.dungeon.is_boss() { self.arenas.remove(&self.dungeon.main) } else { None };
+        let map = match arena {
+            Some(map) => {
+                WorldLocation::burn_arena(engine);
+                map
+            }
+            None => WorldLocation::new(engine, width, height, self.player.exp, floor, &self.dungeon),
+        };
+        let left = std::mem::replace(&mut self.root_map, map);
+        if let Some(depth) = cleared {
+            self.arenas.insert(depth, left);
+        }
+    }
+
     fn restart(&mut self, engine: &mut BTerm) {
         self.player.action(VirtualKeyCode::F3, &mut self.root_map);
         engine.cls();
         self.player.draw(engine);
         self.root_map.draw(&mut self.player, engine);  
-        gui::draw_ui(&self.messages, self.player.clone(), engine);
+        gui::draw_ui(&self.messages, self.player.clone(), self.root_map.boss(), engine);
     }
 
     fn what_is_it(&mut self, engine: &mut BTerm) {
@@ -310,37 +445,75 @@ impl State {
             let xy = (mouse_pos.0 + self.player.pos.0 - (REAL_WIDTH - RIGHT_TABLE_WIDTH) / 2, 
                 mouse_pos.1 + self.player.pos.1 - (REAL_HEIGHT - BOTTOM_TABLE_HEIGHT) / 2);
                 
-            if self.player.visible_tiles.contains(&Point::new(xy.0, xy.1)) {
+            if self.player.sees(xy) {
                 message = match self.root_map.source[xy_idx(xy.0, xy.1, self.root_map.width)] {
-                    TileType::Wall => String::from("It's wall"),
+                    TileType::Wall | TileType::SecretDoor => String::from("It's wall"),
+                    TileType::WeakWall => String::from("It's cracked wall, it could be broken"),
+                    TileType::DamagedWall => String::from("It's crumbling wall"),
+                    TileType::Rubble => String::from("It's pile of rubble"),
+                    TileType::DeepWater => String::from("It's deep water, heavy things will sink in it"),
+                    TileType::ShallowWater => String::from("It's shallow water"),
+                    TileType::Lava => String::from("It's lava, hot enough to burn through boots"),
+                    TileType::Chasm => String::from("It's chasm, you can't see the bottom"),
+                    TileType::Bridge => String::from("It's bridge over the chasm"),
+                    TileType::Mud => String::from("It's sticky mud"),
+                    TileType::Ash => String::from("It's ash, something burned here"),
+                    TileType::Lever | TileType::LeverPulled => String::from("It's lever, it must move something"),
+                    TileType::PressurePlate => String::from("It's pressure plate, something heavy could hold it down"),
+                    TileType::Gate => String::from("It's closed portcullis"),
+                    TileType::GateOpen => String::from("It's raised portcullis"),
+                    TileType::Boulder => String::from("It's boulder, you could push it"),
+                    TileType::TrapSet(trap) => format!("It's your {}, set and waiting", trap.name()),
+                    TileType::Item => {
+                        let items: Vec<String> = self.root_map.items_at(xy_idx(xy.0, xy.1, self.root_map.width))
+                            .into_iter().map(Player::item_name).collect();
+                        format!("It's {}", items.join(", "))
+                    }
+                    TileType::Teleporter => match self.root_map.describe_portal(xy_idx(xy.0, xy.1, self.root_map.width)) {
+                        Some(dest) if self.player.intelligence >= 2 => format!("It's teleporter pad, the runes point {}", dest),
+                        _ => String::from("It's teleporter pad, the runes make no sense to you"),
+                    },
                     TileType::Door => String::from("It's door"),
+                    TileType::Exit if self.dungeon.is_surface() => String::from("It's entrance to the dungeon"),
                     TileType::Exit => String::from("It's ladder to the next dungeon"),
+                    TileType::UpStair if self.dungeon.main == 1 => String::from("It's ladder up to the surface"),
+                    TileType::UpStair => String::from("It's ladder to the floor above"),
+                    TileType::Tree1 | TileType::Tree2 => String::from("It's tree"),
+                    TileType::Campfire => String::from("It's campfire, you could rest by it"),
+                    TileType::Artifact => String::from("It's the legendary treasure of the dungeon!"),
+                    TileType::SealedExit => String::from("It's ladder to the next dungeon, sealed under a heavy grate"),
+                    TileType::BranchStair => match self.dungeon.next_branch() {
+                        Some(i) => format!("It's stairs down into the {}", self.dungeon.branches[i].theme.name()),
+                        None => String::from("It's stairs leading somewhere below"),
+                    },
                     TileType::Coin => String::from("It's pile of old coins"),
                     TileType::Potion => String::from("It's potion"),
                     TileType::BloodStain => String::from("These are bloodstains on the floor"),
                     TileType::BearTrapActived => String::from("It's activated trap"),
+                    TileType::TrapFound(trap) => format!("It's {}, better not to step on it", trap.name()),
                     TileType::Chest => String::from("It's chest"),
-                    TileType::Floor | TileType::BearTrap => {
+                    TileType::LockedChest => String::from("It's locked chest"),
+                    TileType::Floor | TileType::Trap(_) | TileType::TeleporterHidden => {
                         let mut str = String::new();
-                        for entity in &self.root_map.entities {
-                            let ss;
-                            if entity.health > 0 {
-                                ss = format!("{}: {}HP; {} Damage", entity.name().clone(), entity.health.clone(),
-                                    entity.damage.clone());
-                            } else {
-                                ss = format!("dead {}", entity.name().clone())
-                            }
-
-                            for i in -1..2 {
-                                if (entity.x+i, entity.y) == xy {
-                                    str = format!("It's {}", ss)
-                                }
-                            }
-                            for i in -1..2 {
-                                if (entity.x, entity.y+i) == xy {
-                                    str = format!("It's {}", ss)
+                        let near = [xy, (xy.0-1, xy.1), (xy.0+1, xy.1), (xy.0, xy.1-1), (xy.0, xy.1+1)];
+                        let found = near.iter().find_map(|pos| self.root_map.occupant(*pos))
+                            .or_else(|| self.root_map.entities.iter().position(|e| near.contains(&(e.x, e.y))));
+                        if let Some(i) = found {
+                            let entity = &self.root_map.entities[i];
+                            let ss = if entity.is_friendly() {
+                                match entity.stock.first() {
+                                    Some((item, price)) => format!("{}, selling {} for {} coins", entity.name(),
+                                        Player::item_name(item), price),
+                                    None => entity.name(),
                                 }
-                            }
+                            } else if entity.is_bones() {
+                                format!("a pile of {} bones, they are twitching", entity.name().to_lowercase())
+                            } else if entity.health > 0 {
+                                format!("{}: {}HP; {} Damage; {}", entity.name(), entity.health, entity.damage, entity.awareness)
+                            } else {
+                                format!("dead {}", entity.name())
+                            };
+                            str = format!("It's {}", ss);
                         }
                         if self.player.pos == xy {
                             str = String::from("It's you")
@@ -374,6 +547,39 @@ impl State {
             ),
         );
         engine.print_centered(9, format!("Moves done: {}", self.moves));
+        engine.print_centered(11, format!("Score: {}", self.score()));
+    }
+
+    fn victory_screen(&mut self, engine: &mut BTerm) {
+        if self.final_time == 0 {
+            self.final_time = self.total_time.elapsed().as_secs();
+        }
+
+        engine.cls();
+        engine.print_color_centered(1, GOLD, BLACK, "VICTORY!");
+        engine.print_centered(2, "Press Escape to exit.");
+        engine.print_centered(4, "You escaped the dungeon with the legendary treasure!");
+        engine.print_centered(
+            7,
+            format!(
+                "Time elapsed since game start: {} seconds or {} minutes.",
+                self.final_time,
+                self.final_time / 60
+            ),
+        );
+        engine.print_centered(9, format!("Moves done: {}", self.moves));
+        engine.print_color_centered(11, GOLD, BLACK, format!("Score: {}", self.score()));
+    }
+
+    fn score(&self) -> i32 {
+        let mut score = self.player.coins*10 + self.player.exp*2 + self.dungeon.deepest*100;
+        if self.player.artifact {
+            score += 1000;
+        }
+        if self.victory {
+            score += 2000 - (self.moves as i32).min(1000);
+        }
+        score
     }
 }
 
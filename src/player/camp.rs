@@ -0,0 +1,50 @@
+use crate::entity::Entity;
+use super::Player;
+
+const REST_PRICE: i32 = 8;
+
+impl Player {
+    pub fn rest(&mut self) -> String {
+        if self.health >= self.max_health && self.poison == 0 && self.burning == 0 && self.blood == 0 && self.infection == 0 {
+            return String::from("You sit by the campfire for a while")
+        }
+        if self.coins < REST_PRICE {
+            return format!("A night at the camp costs {} coins, you can't afford it", REST_PRICE)
+        }
+
+        self.coins -= REST_PRICE;
+        self.health = self.max_health;
+        self.poison = 0;
+        self.burning = 0;
+        self.infection = 0;
+        self.blood = 0;
+        format!("You pay {} coins, rest by the campfire and wake up fully healed", REST_PRICE)
+    }
+
+    pub fn meet(&mut self, entity: &mut Entity) -> String {
+        if !entity.is_trader() {
+            return entity.greet()
+        }
+
+        let (item, price) = match entity.stock.first() {
+            Some(offer) => offer.clone(),
+            None => return String::from("The trader has nothing left to sell"),
+        };
+        if self.coins < price {
+            return format!("The trader offers {} for {} coins, you can't afford it", Player::item_name(&item), price)
+        }
+        if self.weight + Player::weight_of(&item) > self.max_weight || self.inventory.len() >= 25 {
+            return format!("You can't carry more than {}kg and more than 25 items", self.max_weight)
+        }
+
+        self.coins -= price;
+        self.weight += Player::weight_of(&item);
+        entity.stock.remove(0);
+        let bought = format!("You buy {} for {} coins", Player::item_name(&item), price);
+        self.inventory.push(item);
+        match entity.stock.first() {
+            Some((next, price)) => format!("{}, the trader now offers {} for {} coins", bought, Player::item_name(next), price),
+            None => bought,
+        }
+    }
+}
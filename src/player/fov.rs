@@ -0,0 +1,62 @@
+use bracket_lib::prelude::{line2d_bresenham, BaseMap, DistanceAlg::Pythagoras, Point};
+
+use crate::location::{xy_idx, TileType, WorldLocation};
+
+#[derive(Clone, PartialEq, Default)]
+pub struct Visibility {
+    width: i32,
+    height: i32,
+    near: Vec<u64>,
+    far: Vec<u64>,
+}
+
+impl Visibility {
+    pub fn update(&mut self, origin: (i32, i32), range: i32, far: &[Point], map: &WorldLocation) {
+        let words = map.source.len().div_ceil(64);
+        self.width = map.width;
+        self.height = map.height;
+        self.near = vec![0; words];
+        self.far = vec![0; words];
+
+        let start = Point::new(origin.0, origin.1);
+        for tile in far {
+            if !self.in_bounds(tile.x, tile.y) {
+                continue;
+            }
+
+            let idx = xy_idx(tile.x, tile.y, self.width);
+            self.far[idx / 64] |= 1 << (idx % 64);
+            if Pythagoras.distance2d(start, *tile) <= range as f32 && !line2d_bresenham(start, *tile).iter()
+                .filter(|p| *p != tile && **p != start)
+                .map(|p| xy_idx(p.x, p.y, self.width))
+                .any(|i| map.is_opaque(i) || map.source[i] == TileType::Door) {
+                self.near[idx / 64] |= 1 << (idx % 64);
+            }
+        }
+    }
+
+    fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.width && y < self.height
+    }
+
+    fn get(bits: &[u64], idx: usize) -> bool {
+        bits.get(idx / 64).is_some_and(|word| word >> (idx % 64) & 1 == 1)
+    }
+
+    pub fn is_near(&self, (x, y): (i32, i32)) -> bool {
+        self.in_bounds(x, y) && Visibility::get(&self.near, xy_idx(x, y, self.width))
+    }
+
+    pub fn far_tiles(&self) -> Vec<Point> {
+        let mut tiles = vec![];
+        for (i, word) in self.far.iter().enumerate() {
+            let mut word = *word;
+            while word != 0 {
+                let idx = i*64 + word.trailing_zeros() as usize;
+                tiles.push(Point::new(idx as i32 % self.width, idx as i32 / self.width));
+                word &= word - 1;
+            }
+        }
+        tiles
+    }
+}
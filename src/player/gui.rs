@@ -1,12 +1,17 @@
 use crate::{REAL_HEIGHT, REAL_WIDTH};
-use bracket_lib::{color::{CYAN, CYAN1, CYAN3, GOLD, GREEN3, HOT_PINK, PURPLE, WHITESMOKE}, terminal::{to_cp437, BTerm, BLACK, RED, RGB, WHITE, YELLOW}};
-use super::{ItemType, Player};
+use bracket_lib::{color::{CHARTREUSE, CYAN, DIM_GRAY, CYAN1, CYAN3, GOLD, GREEN3, HOT_PINK, OLIVE_DRAB, ORANGE, PURPLE, WHITESMOKE}, terminal::{to_cp437, BTerm, BLACK, RED, RGB, WHITE, YELLOW}};
+use crate::{entity::Entity, location::branch::Dungeon};
+use super::{lockpick::LOCK_SIZE, ItemType, Player};
 
 pub const RIGHT_TABLE_WIDTH: i32 = 23;
 pub const BOTTOM_TABLE_HEIGHT: i32 = 9;
 const EQUIPMENT_TABLE_WIDTH: i32 = 25;
 const INVENTORY_TABLE_WIDTH: i32 = REAL_WIDTH-RIGHT_TABLE_WIDTH-EQUIPMENT_TABLE_WIDTH-1;
 const INVENTORY_TABLE_HEIGHT: i32 = REAL_HEIGHT-BOTTOM_TABLE_HEIGHT-2;
+const LOCK_TABLE_WIDTH: i32 = 34;
+const LOCK_TABLE_HEIGHT: i32 = 7;
+const OVERVIEW_TABLE_WIDTH: i32 = 48;
+const OVERVIEW_TABLE_HEIGHT: i32 = REAL_HEIGHT-BOTTOM_TABLE_HEIGHT-2;
 
 pub fn draw_inventory(engine: &mut BTerm, player: &mut Player, wheel: i32) {
     engine.draw_box(0, -1,
@@ -43,6 +48,9 @@ pub fn draw_inventory(engine: &mut BTerm, player: &mut Player, wheel: i32) {
                 (format!("{} {}", material, weapon_type.to_string().to_lowercase()), 
                 format!("Damage: {} Weight: {:.1}kg", damage, 
                 Player::weight_by_weapon(weapon_type, material.clone()))),
+            super::ItemType::TrapKit(trap) => 
+                (format!("{} kit", trap.name()), 
+                format!("Weight: {:.1}kg", Player::weight_of(&player.inventory[i]))),
         };
         let mut j = 0;
         if i >= 8 {
@@ -72,11 +80,76 @@ pub fn draw_inventory(engine: &mut BTerm, player: &mut Player, wheel: i32) {
         yy += 2;
         cont = !cont;
     }
+    draw_line_inventory(engine, String::from("Shift+letter to drop"), CYAN, INVENTORY_TABLE_HEIGHT-4);
     draw_line_inventory(engine, format!("Inventory:"), CYAN, INVENTORY_TABLE_HEIGHT-3);
     draw_line_inventory(engine, format!("PageUp/PageDown for list"), CYAN,INVENTORY_TABLE_HEIGHT-2);
     draw_line_inventory(engine, format!("Press I to close"), CYAN,INVENTORY_TABLE_HEIGHT-1);
 }
 
+pub fn draw_lock(engine: &mut BTerm, player: &Player) {
+    if let Some(lock) = &player.lock {
+        let x = (REAL_WIDTH-RIGHT_TABLE_WIDTH-LOCK_TABLE_WIDTH)/2;
+        let y = (REAL_HEIGHT-BOTTOM_TABLE_HEIGHT-LOCK_TABLE_HEIGHT)/2;
+        engine.draw_box(x, y, LOCK_TABLE_WIDTH, LOCK_TABLE_HEIGHT,
+            RGB::named(WHITE),
+            RGB::named(BLACK));
+
+        engine.print_color(x+1, y+1, PURPLE, BLACK, 
+            format!("Lockpicking, attempts: {}", lock.attempts));
+        
+        let left = x+1+(LOCK_TABLE_WIDTH-1-LOCK_SIZE)/2;
+        for i in 0..LOCK_SIZE {
+            let (symbol, color) = match lock.hint {
+                Some((from, to)) if (from..=to).contains(&i) => ('=', CYAN),
+                _ => ('-', WHITE),
+            };
+            engine.set(left+i, y+3, RGB::named(color), RGB::named(BLACK), to_cp437(symbol));
+        }
+        engine.set(left+lock.cursor, y+3, RGB::named(YELLOW), RGB::named(BLACK), to_cp437('|'));
+
+        engine.print_color(x+1, y+5, WHITE, BLACK, "Left/Right to move the pick");
+        engine.print_color(x+1, y+6, WHITE, BLACK, "Space to turn, Back to stop");
+    }
+}
+
+pub fn draw_overview(engine: &mut BTerm, dungeon: &Dungeon) {
+    engine.draw_box(0, 0, OVERVIEW_TABLE_WIDTH, OVERVIEW_TABLE_HEIGHT,
+        RGB::named(WHITE),
+        RGB::named(BLACK));
+    engine.print_color(2, 1, CYAN, BLACK, "Dungeon overview");
+
+    let rows = OVERVIEW_TABLE_HEIGHT-5;
+    let mut y = 3;
+    if dungeon.main < rows {
+        let (line, fg) = if dungeon.is_surface() { ("@ Surface", YELLOW) } else { ("  Surface", WHITE) };
+        engine.print_color(2, y, fg, BLACK, line);
+        y += 1;
+    }
+    for floor in (dungeon.main-rows+2).max(1)..=dungeon.main {
+        let (line, fg) = if dungeon.current.is_none() && floor == dungeon.main {
+            (format!("@ Floor {}", floor), YELLOW)
+        } else {
+            (format!("  Floor {}", floor), WHITE)
+        };
+        engine.print_color(2, y, fg, BLACK, line);
+
+        for (i, branch) in dungeon.branches.iter().enumerate().filter(|(_, b)| b.entry == floor) {
+            let (line, fg) = if dungeon.current == Some(i) {
+                (format!("@ {} {}/{}", branch.theme.name(), dungeon.level, branch.length), YELLOW)
+            } else if branch.cleared {
+                (format!("  {} (cleared)", branch.theme.name()), GREEN3)
+            } else {
+                (format!("  {} ({} floors)", branch.theme.name(), branch.length), HOT_PINK)
+            };
+            engine.print_color(14, y, WHITE, BLACK, "└");
+            engine.print_color(15, y, fg, BLACK, line);
+        }
+        y += 1;
+    }
+    engine.print_color(2, y, WHITESMOKE, BLACK, format!("  Floor {}: ???", dungeon.main+1));
+    engine.print_color(2, OVERVIEW_TABLE_HEIGHT-1, CYAN, BLACK, "Press Tab to close");
+}
+
 fn draw_line_inventory(engine: &mut BTerm, string: String, fg: (u8, u8, u8), y: i32) {
     engine.print_color(
         1, 0+y+1,
@@ -138,7 +211,7 @@ fn draw_message_bar(engine: &mut BTerm, messages: &[String]) {
     }
 }
 
-fn draw_right_bar(engine: &mut BTerm, player: Player) {
+fn draw_right_bar(engine: &mut BTerm, player: Player, boss: Option<&Entity>) {
     engine.draw_box(
         REAL_WIDTH - 1 - RIGHT_TABLE_WIDTH as i32,
         0,
@@ -166,15 +239,51 @@ fn draw_right_bar(engine: &mut BTerm, player: Player) {
         GOLD, BLACK,  5);
     draw_line_right(engine, format!("EXP:{}", player.exp), 
         GREEN3, BLACK,  6);
+    if player.stolen_coins > 0 || player.stolen_items > 0 {
+        let mut robbed = vec![];
+        if player.stolen_coins > 0 {
+            robbed.push(format!("-{}$", player.stolen_coins));
+        }
+        if player.stolen_items > 0 {
+            robbed.push(format!("{} item{}", player.stolen_items, if player.stolen_items > 1 { "s" } else { "" }));
+        }
+        draw_line_right(engine, format!("Robbed:{}", robbed.join(" ")), RED, BLACK, 7);
+    }
 
 
     draw_line_right(engine, format!("Lockpick:{}%", player.lockpick), 
         PURPLE, BLACK,  8);
+    draw_line_right(engine, format!("Lockpicks:{}", player.picks), 
+        PURPLE, BLACK,  9);
+
+    if player.burning > 0 {
+        draw_line_right(engine, String::from("Burning"), ORANGE, BLACK, 11);
+    } else if player.poison > 0 {
+        draw_line_right(engine, String::from("Poisoned"), CHARTREUSE, BLACK, 11);
+    } else if player.infection > 0 {
+        draw_line_right(engine, String::from("Infected"), OLIVE_DRAB, BLACK, 11);
+    }
+
+    if player.artifact {
+        draw_line_right(engine, String::from("Carrying the treasure"), GOLD, BLACK, 12);
+    }
+
+    if let Some(boss) = boss {
+        draw_line_right(engine, boss.name(), RED, BLACK, 13);
+        let width = RIGHT_TABLE_WIDTH-2;
+        let filled = (boss.health.max(0) * width + boss.max_health - 1) / boss.max_health;
+        for i in 0..width {
+            let color = if i < filled { RED } else { DIM_GRAY };
+            engine.set(REAL_WIDTH-RIGHT_TABLE_WIDTH+i, 15, RGB::named(color), RGB::named(BLACK), to_cp437('█'));
+        }
+        draw_line_right(engine, format!("HP:{}/{} Phase:{}", boss.health, boss.max_health, boss.phase),
+            WHITESMOKE, BLACK, 15);
+    }
 }
 
-pub fn draw_ui(messages: &[String], player: Player, engine: &mut BTerm) {
+pub fn draw_ui(messages: &[String], player: Player, boss: Option<&Entity>, engine: &mut BTerm) {
     draw_message_bar(engine, messages);
-    draw_right_bar(engine, player);
+    draw_right_bar(engine, player, boss);
     engine.set(REAL_WIDTH-RIGHT_TABLE_WIDTH-1, REAL_HEIGHT-BOTTOM_TABLE_HEIGHT-1, 
         RGB::named(WHITE), RGB::named(BLACK), to_cp437('┴'));
     engine.set(REAL_WIDTH-RIGHT_TABLE_WIDTH+RIGHT_TABLE_WIDTH-1, REAL_HEIGHT-BOTTOM_TABLE_HEIGHT-1, 
@@ -0,0 +1,128 @@
+use bracket_lib::{random::RandomNumberGenerator, terminal::VirtualKeyCode};
+
+use crate::location::{TileType, WorldLocation};
+use super::Player;
+
+pub const LOCK_SIZE: i32 = 21;
+const LOCK_ATTEMPTS: i32 = 3;
+
+#[derive(Clone, PartialEq)]
+pub struct Lock {
+    pub idx: usize,
+    pub cursor: i32,
+    pub attempts: i32,
+    pub hint: Option<(i32, i32)>,
+    target: i32,
+    tolerance: i32,
+}
+
+impl Lock {
+    pub fn new(idx: usize, agility: i32, intelligence: i32) -> Self {
+        let mut rng = RandomNumberGenerator::new();
+        let target = rng.range(0, LOCK_SIZE);
+
+        let hint = if intelligence > 0 {
+            let spread = 6 - intelligence;
+            let left = target - rng.range(0, spread + 1);
+            Some((left.max(0), (left + spread).min(LOCK_SIZE - 1)))
+        } else {
+            None
+        };
+
+        Self {
+            idx,
+            cursor: LOCK_SIZE / 2,
+            attempts: LOCK_ATTEMPTS,
+            hint,
+            target,
+            tolerance: 1 + agility / 2,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        (self.cursor - self.target).abs() <= self.tolerance
+    }
+}
+
+impl Player {
+    pub fn pick_lock(&mut self, key: VirtualKeyCode, map: &mut WorldLocation) -> String {
+        let mut lock = match self.lock.clone() {
+            Some(lock) => lock,
+            None => return String::new(),
+        };
+
+        let message = match key {
+            VirtualKeyCode::Left | VirtualKeyCode::A => {
+                lock.cursor = (lock.cursor - 1).max(0);
+                String::new()
+            }
+            VirtualKeyCode::Right | VirtualKeyCode::D => {
+                lock.cursor = (lock.cursor + 1).min(LOCK_SIZE - 1);
+                String::new()
+            }
+            VirtualKeyCode::Back => {
+                self.lock = None;
+                return String::from("You put the lockpick away")
+            }
+            VirtualKeyCode::Space | VirtualKeyCode::Return => {
+                if lock.is_open() {
+                    self.lock = None;
+                    map.unlock(lock.idx);
+                    return String::from("The lock clicks open")
+                }
+                self.failed_pick(&mut lock, map)
+            }
+            _ => String::new(),
+        };
+
+        if self.lock.is_some() {
+            self.lock = Some(lock);
+        }
+        message
+    }
+
+    fn failed_pick(&mut self, lock: &mut Lock, map: &mut WorldLocation) -> String {
+        let mut rng = RandomNumberGenerator::new();
+        lock.attempts -= 1;
+
+        let mut message = if self.intelligence >= 2 {
+            if lock.cursor < lock.target {
+                String::from("The pins give a little further to the right")
+            } else {
+                String::from("The pins give a little further to the left")
+            }
+        } else {
+            String::from("The pins don't move")
+        };
+
+        if rng.range(1, 101) <= 40 - self.agility * 5 + (self.weight / 2.0) as i32 {
+            map.make_noise(self.pos, 10);
+            message = format!("{}\nThe lock rattles loudly!", message);
+        }
+
+        if rng.range(1, 101) <= 35 - self.agility * 5 {
+            self.picks -= 1;
+            self.lock = None;
+            format!("{}\nYour lockpick breaks, {} left", message, self.picks)
+        } else if lock.attempts <= 0 {
+            self.lock = None;
+            format!("{}\nYou lose the feel of the pins and give up", message)
+        } else {
+            message
+        }
+    }
+
+    pub fn force_lock(&mut self, idx: usize, map: &mut WorldLocation) -> String {
+        if RandomNumberGenerator::new().range(1, 101) <= self.lockpick {
+            let message = match map.source[idx] {
+                TileType::LockedChest => String::from("You force the chest open"),
+                _ => String::from("You broke down the door"),
+            };
+            map.unlock(idx);
+            message
+        } else {
+            map.jam(idx);
+            String::new()
+        }
+    }
+}
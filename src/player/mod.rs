@@ -1,13 +1,17 @@
 use std::{cmp::max, fmt};
 
 use bracket_lib::{
-    color::{HOTPINK, ORANGE_RED, RED, WHITESMOKE}, prelude::field_of_view, random::RandomNumberGenerator, terminal::{to_cp437, BTerm, Point, VirtualKeyCode, BLACK, RGB, WHITE}
+    color::{CHARTREUSE, GOLD, HOTPINK, OLIVE_DRAB, ORANGE, ORANGE_RED, RED}, prelude::field_of_view, random::RandomNumberGenerator, terminal::{to_cp437, BTerm, Point, VirtualKeyCode, BLACK, RGB, WHITE}
 };
 
+pub mod camp;
+pub mod fov;
 pub mod gui;
-use crate::{location::{xy_idx, TileType, WorldLocation}, REAL_HEIGHT, REAL_WIDTH};
+pub mod lockpick;
+pub mod trap;
+use crate::{entity::awareness::Awareness, location::{item::LURE_RADIUS, trap::TrapType, xy_idx, TileType, WorldLocation}, REAL_HEIGHT, REAL_WIDTH};
 
-use self::gui::{BOTTOM_TABLE_HEIGHT, RIGHT_TABLE_WIDTH};
+use self::{fov::Visibility, gui::{BOTTOM_TABLE_HEIGHT, RIGHT_TABLE_WIDTH}, lockpick::Lock};
 
 #[derive(Clone, PartialEq)]
 pub struct Player {
@@ -16,13 +20,20 @@ pub struct Player {
     pub max_health: i32,
     pub coins: i32,
     pub exp: i32,
-    pub visible_tiles: Vec<Point>,
-    pub visible_tiles_far: Vec<Point>,
+    pub fov: Visibility,
     pub damage: i32,
     pub weapon: Option<ItemType>,
     pub inventory: Vec<ItemType>,
     pub kchance: i32, 
     pub lockpick: i32,
+    pub picks: i32,
+    pub lock: Option<Lock>,
+    pub placing: Option<TrapType>,
+    pub minigame: bool,
+    pub descend: bool,
+    pub branch: bool,
+    pub ascend: bool,
+    pub artifact: bool,
     pub luck: i32, 
     pub strength: i32, 
     pub intelligence: i32, 
@@ -30,10 +41,16 @@ pub struct Player {
     pub fov_range: i32,
     pub weight: f32,
     pub max_weight: f32,
-    blood: i32,
+    pub poison: i32,
+    pub burning: i32,
+    pub infection: i32,
+    pub stolen_coins: i32,
+    pub stolen_items: usize,
+    pub blood: i32,
 }
 
 const SMALL_POTION_WEIGHT: f32 = 0.7;
+const TRAP_KIT_WEIGHT: f32 = 1.0;
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum WeaponType {
@@ -41,6 +58,7 @@ pub enum WeaponType {
     Axe, 
     Dagger,
     Sabber,
+    Mace,
 }
 
 #[derive(PartialEq, Clone, Debug)]
@@ -61,6 +79,7 @@ pub enum Material {
 pub enum ItemType {
     Potion(Size, (u8, u8, u8)),
     Weapon(WeaponType, Material, i32),
+    TrapKit(TrapType),
 }
 
 impl fmt::Display for ItemType {
@@ -92,8 +111,7 @@ impl Player {
         luck: i32, strength: i32, intelligence: i32, agility: i32) -> Self {
         Self {
             pos: (x, y),
-            visible_tiles: vec![],
-            visible_tiles_far: vec![],
+            fov: Visibility::default(),
             fov_range,
             coins: 2+luck/2,
             exp: 0,
@@ -103,6 +121,14 @@ impl Player {
             kchance: 1+luck/2,
             inventory: vec![],
             lockpick: 2+agility*2+intelligence/2, 
+            picks: 1+agility/2,
+            lock: None,
+            placing: None,
+            minigame: true,
+            descend: false,
+            branch: false,
+            ascend: false,
+            artifact: false,
             luck: luck, 
             strength: strength, 
             intelligence: intelligence,
@@ -110,43 +136,102 @@ impl Player {
             weight: 0.0,
             weapon: None,
             max_weight: (20+strength*2+luck/2) as f32,
+            poison: 0,
+            burning: 0,
+            infection: 0,
+            stolen_coins: 0,
+            stolen_items: 0,
             blood: 0,
         }
     }
 
     fn fight(&mut self, xy: (i32, i32), map: &mut WorldLocation) -> String {
-        for entity in &mut map.entities {
-            if entity.x == xy.0 && entity.y == xy.1 {
-                let mut ss = String::new();
-                if entity.health > 0 {
-                    if RandomNumberGenerator::new().range(1, 101) <= self.kchance {
-                        entity.health -= self.damage*2;
-                        ss = format!("Player critically hits {}, for {}HP", entity.name(), self.damage*2)
-                    } else {
-                        entity.health -= self.damage;
-                        ss = format!("Player hits {}, for {}HP", entity.name(), self.damage)
-                    }
-                }
-                if entity.health <= 0 {
-                    self.exp += 5;
-                    return format!("{} and kills {} +5EXP", ss, entity.name())
+        if let Some(i) = map.occupant(xy) {
+            let entity = &mut map.entities[i];
+            if entity.is_friendly() {
+                return self.meet(entity)
+            }
+            let ss = if RandomNumberGenerator::new().range(1, 101) <= self.kchance {
+                entity.health -= self.damage*2;
+                format!("Player critically hits {}, for {}HP", entity.name(), self.damage*2)
+            } else {
+                entity.health -= self.damage;
+                format!("Player hits {}, for {}HP", entity.name(), self.damage)
+            };
+            entity.awareness = Awareness::Alerted;
+            if entity.health <= 0 {
+                let name = entity.name();
+                if entity.collapse(&self.weapon) {
+                    map.vacate(xy);
+                    return format!("{} and {} collapses into a pile of bones", ss, name)
                 }
-                return ss
+                let exp = if entity.is_boss() { 50 } else { 5 };
+                self.exp += exp;
+                map.vacate(xy);
+                return format!("{} and kills {} +{}EXP", ss, name, exp)
             }
+            return ss
         }
-        if map.source[xy_idx(xy.0, xy.1, map.width)] == TileType::Door {
-            if RandomNumberGenerator::new().range(1, 101) <= self.lockpick &&
-                map.colors[xy_idx(xy.0, xy.1, map.width)] == WHITESMOKE {
-                    map.source[xy_idx(xy.0, xy.1, map.width)] = TileType::Floor;
-                    return format!("You broke down the door")
-            } else {
-                map.colors[xy_idx(xy.0, xy.1, map.width)] = WHITE;
+        let idx = xy_idx(xy.0, xy.1, map.width);
+        if matches!(map.source[idx], TileType::Lever | TileType::LeverPulled) {
+            return map.pull_lever(idx, self)
+        }
+        if map.source[idx] == TileType::Boulder {
+            let to = (xy.0*2-self.pos.0, xy.1*2-self.pos.1);
+            if map.push_boulder(idx, xy_idx(to.0, to.1, map.width)) {
+                self.pos = xy;
+                self.update_fov(map);
+                return String::from("You push the boulder")
+            }
+            return String::from("The boulder won't budge")
+        }
+        if map.source[idx] == TileType::Campfire {
+            return self.rest()
+        }
+        if map.source[idx] == TileType::SealedExit {
+            return String::from("The grate won't move while the boss is alive")
+        }
+        if map.is_breakable(idx) {
+            let mut damage = 1+self.strength;
+            if let Some(ItemType::Weapon(WeaponType::Axe, _, _)) = self.weapon {
+                damage += 4;
+            }
+
+            if map.damage_wall(idx, damage) {
+                return String::from("The wall collapses into rubble")
+            } else if map.source[idx] == TileType::DamagedWall {
+                return String::from("The wall crumbles under your blows")
+            }
+            return String::from("You hit the wall, it cracks")
+        }
+        if map.is_locked(idx) {
+            if map.is_jammed(idx) {
+                return String::from("The lock is jammed")
+            } else if self.minigame && self.picks > 0 {
+                self.lock = Some(Lock::new(idx, self.agility, self.intelligence));
+                return String::from("You take out a lockpick...")
             }
+            return self.force_lock(idx, map)
         }
             
         String::new()
     }
 
+    pub fn drop_item(&mut self, i: i32, map: &mut WorldLocation) -> String {
+        if i >= self.inventory.len() as i32 || i < 0 {
+            return String::new()
+        }
+
+        let item = self.inventory[i as usize].clone();
+        if !map.drop_item(xy_idx(self.pos.0, self.pos.1, map.width), item.clone()) {
+            return String::from("You can't drop anything here")
+        }
+        self.inventory.remove(i as usize);
+        self.weight -= Player::weight_of(&item);
+        map.make_noise(self.pos, LURE_RADIUS);
+        format!("You drop {}, it clatters on the floor", Player::item_name(&item))
+    }
+
     pub fn use_inventory(&mut self, i: i32) -> String {
         let mut s = String::new();
         if i < self.inventory.len() as i32 && i >= 0  {
@@ -197,6 +282,11 @@ impl Player {
                             weapon_type.to_string().to_lowercase(), damage)
                     }
                 }
+                ItemType::TrapKit(trap) => {
+                    self.weight -= Player::weight_of(&ItemType::TrapKit(*trap));
+                    self.placing = Some(*trap);
+                    format!("Choose a direction to set the {}, Back to cancel", trap.name())
+                }
             };
             self.inventory.remove(i as usize);        
         }
@@ -218,6 +308,15 @@ impl Player {
             VirtualKeyCode::S => self.try_move(map, 0, 1),
 
             VirtualKeyCode::F3 => self.try_move(map, 0, 0),
+            VirtualKeyCode::F => {
+                let found = self.search(map, 2, 30+self.intelligence*10);
+                if found.is_empty() {
+                    String::from("You search around but find nothing")
+                } else {
+                    found
+                }
+            }
+            VirtualKeyCode::X => self.disarm(map),
             _ => String::new(),
         }
     }
@@ -226,6 +325,12 @@ impl Player {
         let mut color = WHITE;
         if self.blood > 0 {
             color = RED;
+        } else if self.burning > 0 {
+            color = ORANGE;
+        } else if self.poison > 0 {
+            color = CHARTREUSE;
+        } else if self.infection > 0 {
+            color = OLIVE_DRAB;
         }
 
         engine.set(
@@ -253,6 +358,26 @@ impl Player {
         }
     }
 
+    pub fn weight_of(item: &ItemType) -> f32 {
+        match item {
+            ItemType::Potion(size, _) => Player::weight_by_size(size.clone()),
+            ItemType::Weapon(weapon_type, material, _) => 
+                Player::weight_by_weapon(weapon_type.clone(), material.clone()),
+            ItemType::TrapKit(TrapType::Bear) => TRAP_KIT_WEIGHT*2.0,
+            ItemType::TrapKit(_) => TRAP_KIT_WEIGHT,
+        }
+    }
+
+    pub fn item_name(item: &ItemType) -> String {
+        match item {
+            ItemType::Potion(size, color) => format!("{} {} potion", 
+                size.to_string().to_lowercase(), Player::color_to_str(*color)),
+            ItemType::Weapon(weapon_type, material, _) => format!("{} {}", 
+                material.to_string().to_lowercase(), weapon_type.to_string().to_lowercase()),
+            ItemType::TrapKit(trap) => format!("{} kit", trap.name()),
+        }
+    }
+
     pub fn weight_by_weapon(weapon_type: WeaponType, material: Material) -> f32 {
         let weight = 0.3 + match material {
             Material::Bronze => 0.4,
@@ -263,14 +388,33 @@ impl Player {
         weight + match weapon_type {
             WeaponType::Sword => 0.5,
             WeaponType::Axe => 1.5,
+            WeaponType::Mace => 1.0,
             _ => 0.0,
         }
     }
 
+    fn search(&mut self, map: &mut WorldLocation, radius: i32, chance: i32) -> String {
+        let mut found: Vec<String> = vec![];
+        for y in self.pos.1-radius..=self.pos.1+radius {
+            for x in self.pos.0-radius..=self.pos.0+radius {
+                if x < 0 || y < 0 || x >= map.width || y >= map.height {
+                    continue;
+                }
+
+                let idx = xy_idx(x, y, map.width);
+                if map.is_hidden(idx) && RandomNumberGenerator::new().range(1, 101) <= chance - map.hide_difficulty(idx) {
+                    found.push(map.reveal(idx));
+                }
+            }
+        }
+        found.join("\n")
+    }
+
     fn event(&mut self, map: &mut WorldLocation) -> String {
         let message = match map.source[xy_idx(self.pos.0, self.pos.1, map.width)] {
             TileType::Coin => { 
-                let coins = RandomNumberGenerator::new().range(1, 4);
+                let coins = map.purses.remove(&xy_idx(self.pos.0, self.pos.1, map.width))
+                    .unwrap_or_else(|| RandomNumberGenerator::new().range(1, 4));
                 self.coins += coins; 
                 format!("You pick up {} coins", coins)
             },
@@ -294,23 +438,98 @@ impl Player {
                     }
                 }
             },
+            TileType::Item => {
+                let idx = xy_idx(self.pos.0, self.pos.1, map.width);
+                let mut picked: Vec<String> = vec![];
+                for item in map.take_items(idx) {
+                    if self.weight + Player::weight_of(&item) <= self.max_weight && self.inventory.len() < 25 {
+                        self.weight += Player::weight_of(&item);
+                        picked.push(Player::item_name(&item));
+                        self.inventory.push(item);
+                    } else {
+                        map.drop_item(idx, item);
+                    }
+                }
+
+                if picked.is_empty() {
+                    format!("You can't carry more than {}kg and more than 25 items", self.max_weight)
+                } else {
+                    format!("You pick up {}", picked.join(", "))
+                }
+            }
             TileType::Exit => {
+                self.descend = true;
                 format!("You go down to the floor below...")
             }
-            TileType::BearTrap => {
-                map.source[xy_idx(self.pos.0, self.pos.1, map.width)] = TileType::BearTrapActived;
-                if RandomNumberGenerator::new().range(1, 101) <= 
-                    50-self.agility*2+(self.weight/2.0) as i32 {
-                    self.health -= self.max_health/4;
-                    self.blood += 5;
-                    format!("You fall into a trap and you bleed out for 5 moves, -{}HP", self.max_health/4)
+            TileType::UpStair => {
+                self.ascend = true;
+                String::from("You climb up to the floor above...")
+            }
+            TileType::Artifact => {
+                self.artifact = true;
+                map.source[xy_idx(self.pos.0, self.pos.1, map.width)] = TileType::Floor;
+                map.source[xy_idx(map.start_x, map.start_y, map.width)] = TileType::UpStair;
+                map.colors[xy_idx(map.start_x, map.start_y, map.width)] = WHITE;
+                String::from("You lift the legendary treasure! Now fight your way back up to the surface")
+            }
+            TileType::BranchStair => {
+                self.descend = true;
+                self.branch = true;
+                String::from("You go down the side stairs...")
+            }
+            TileType::Trap(trap) | TileType::TrapFound(trap) | TileType::TrapSet(trap) => {
+                self.spring_trap(trap, xy_idx(self.pos.0, self.pos.1, map.width), map)
+            }
+            TileType::Teleporter | TileType::TeleporterHidden => {
+                let idx = xy_idx(self.pos.0, self.pos.1, map.width);
+                match map.portal_of(idx) {
+                    Some(dest) if !map.is_solid((dest as i32 % map.width, dest as i32 / map.width)) => {
+                        map.reveal(idx);
+                        map.reveal(dest);
+                        map.make_noise(self.pos, 6);
+                        self.pos = (dest as i32 % map.width, dest as i32 / map.width);
+                        self.update_fov(map);
+                        String::from("The runes flare and you are pulled through the floor")
+                    }
+                    _ => String::from("The runes flicker, something blocks the other side"),
+                }
+            }
+            TileType::Chasm => {
+                let damage = self.max_health/10;
+                self.health -= damage;
+                self.descend = true;
+                format!("You fall into the chasm to the floor below, -{}HP", damage)
+            }
+            TileType::Lava => {
+                let damage = self.max_health/5+2;
+                self.health -= damage;
+                format!("The lava burns you, -{}HP", damage)
+            }
+            TileType::DeepWater => {
+                if self.weight > self.max_weight/2.0 && !self.inventory.is_empty() {
+                    let mut heaviest = 0;
+                    for i in 0..self.inventory.len() {
+                        if Player::weight_of(&self.inventory[i]) > Player::weight_of(&self.inventory[heaviest]) {
+                            heaviest = i;
+                        }
+                    }
+
+                    let item = self.inventory.remove(heaviest);
+                    self.weight -= Player::weight_of(&item);
+                    format!("You swim, but your {} is too heavy and sinks to the bottom", Player::item_name(&item))
                 } else {
-                    format!("Trap doesn't work so you don't fall into a trap")
+                    String::from("You swim through deep water")
                 }
             }
             TileType::Chest => {
+                let bonus = if map.colors[xy_idx(self.pos.0, self.pos.1, map.width)] == GOLD {
+                    4
+                } else {
+                    0
+                };
+
                 let mut damage = 1;
-                let material = match RandomNumberGenerator::new().range(1, 11) {
+                let material = match RandomNumberGenerator::new().range(1+bonus, 11) {
                     0..=5 => Material::Bronze,
                     6..=7 => {
                         damage *= 3;
@@ -321,13 +540,17 @@ impl Player {
                         Material::Silver
                     },
                 };
-                let weapon_type = match RandomNumberGenerator::new().range(1, 11) {
+                let weapon_type = match RandomNumberGenerator::new().range(1+bonus, 11) {
                     0..=5 => WeaponType::Dagger,
                     6..=7 => {
                         damage *= 3;
                         WeaponType::Sword
                     }
-                    8..=9 => {
+                    8 => {
+                        damage *= 3;
+                        WeaponType::Mace
+                    }
+                    9 => {
                         damage *= 4;
                         WeaponType::Axe
                     },
@@ -337,7 +560,7 @@ impl Player {
                     }
                 };
                 
-                let size = match RandomNumberGenerator::new().range(1, 11) {
+                let size = match RandomNumberGenerator::new().range(1+bonus, 11) {
                     0..=6 => Size::Small,
                     7..=8 => Size::Average,
                     _ => Size::Large,
@@ -352,24 +575,42 @@ impl Player {
                                 weapon_type.clone(), 
                                 material.clone()) + Player::weight_by_size(size.clone());
                         map.source[xy_idx(self.pos.0, self.pos.1, map.width)] = TileType::Floor;
-                        format!("You pick up {} {} and {} {} potion from chest", 
+
+                        let mut picks = String::new();
+                        if RandomNumberGenerator::new().range(1, 11) <= 3 {
+                            self.picks += 1;
+                            picks = String::from("\nThere is also a lockpick inside");
+                        }
+                        format!("You pick up {} {} and {} {} potion from chest{}", 
                             material.to_string().to_lowercase(), 
                             weapon_type.to_string().to_lowercase(), 
                             size.to_string().to_lowercase(), 
-                            Player::color_to_str(ORANGE_RED))
+                            Player::color_to_str(ORANGE_RED), picks)
                 } else {
                     format!("You can't carry more than {}kg and more than 25 items", self.max_weight)
                 }
             }
             _ => String::new(),
         };
-        if !matches!(map.source[xy_idx(self.pos.0, self.pos.1, map.width)], 
-            TileType::BloodStain | TileType::BearTrapActived | TileType::Chest) {
+        if matches!(map.source[xy_idx(self.pos.0, self.pos.1, map.width)], 
+            TileType::Coin | TileType::Potion) {
             map.source[xy_idx(self.pos.0, self.pos.1, map.width)] = TileType::Floor;
         }
         message
     }
 
+    pub fn update_fov(&mut self, map: &mut WorldLocation) {
+        let far = field_of_view(
+            Point::new(self.pos.0, self.pos.1), 
+                max(REAL_WIDTH-RIGHT_TABLE_WIDTH, REAL_HEIGHT-BOTTOM_TABLE_HEIGHT), 
+                map);
+        self.fov.update(self.pos, self.fov_range, &far, map);
+    }
+
+    pub fn sees(&self, pos: (i32, i32)) -> bool {
+        self.fov.is_near(pos)
+    }
+
     fn try_move(&mut self, map: &mut WorldLocation, delta_x: i32, delta_y: i32) -> String {
         if self.blood > 0 {
             self.health -= 1+self.max_health/50;
@@ -380,22 +621,52 @@ impl Player {
             }
             self.blood -= 1;
         }
+        if self.poison > 0 {
+            self.health -= 1;
+            self.poison -= 1;
+        }
+        if self.infection > 0 {
+            self.infection -= 1;
+            if self.infection % 4 == 0 {
+                self.health -= 2;
+            }
+        }
+        if self.burning > 0 {
+            if matches!(map.source[xy_idx(self.pos.0, self.pos.1, map.width)], 
+                TileType::DeepWater | TileType::ShallowWater) {
+                self.burning = 0;
+            } else {
+                self.health -= 2;
+                self.burning -= 1;
+            }
+        }
+
+        let stumble = match map.source[xy_idx(self.pos.0, self.pos.1, map.width)] {
+            TileType::Rubble => "You stumble over the rubble",
+            TileType::Mud => "You get stuck in the mud",
+            TileType::DeepWater => "You struggle to keep afloat",
+            _ => "",
+        };
+        if !stumble.is_empty() && (delta_x, delta_y) != (0, 0) &&
+            RandomNumberGenerator::new().range(1, 101) <= 40-self.agility*5 {
+            return String::from(stumble)
+        }
 
         let x = self.pos.0 + delta_x;
         let y = self.pos.1 + delta_y;
         if !map.is_solid((x, y)) {
             self.pos.0 = x;
             self.pos.1 = y;
-            self.visible_tiles =
-                field_of_view(Point::new(self.pos.0, self.pos.1), self.fov_range, map);
-            map.far = true;
-            self.visible_tiles_far = field_of_view(
-                Point::new(self.pos.0, self.pos.1), 
-                    max(REAL_WIDTH-RIGHT_TABLE_WIDTH, REAL_HEIGHT-BOTTOM_TABLE_HEIGHT), 
-                    map);
-            map.far = false;
-
-            return self.event(map);
+            self.update_fov(map);
+
+            let message = self.event(map);
+            let found = self.search(map, 1, self.intelligence*5);
+            if found.is_empty() {
+                return message;
+            } else if message.is_empty() {
+                return found;
+            }
+            format!("{}\n{}", message, found)
         } else {
             return self.fight((x, y), map);
         }
@@ -0,0 +1,127 @@
+use bracket_lib::{random::RandomNumberGenerator, terminal::VirtualKeyCode};
+
+use crate::location::{trap::TrapType, xy_idx, TileType, WorldLocation};
+use super::{ItemType, Player};
+
+impl Player {
+    pub fn spring_trap(&mut self, trap: TrapType, idx: usize, map: &mut WorldLocation) -> String {
+        let mut rng = RandomNumberGenerator::new();
+        map.set_trap_found(idx, trap);
+
+        match trap {
+            TrapType::Bear => {
+                map.source[idx] = TileType::BearTrapActived;
+                if rng.range(1, 101) <= 50-self.agility*2+(self.weight/2.0) as i32 {
+                    self.health -= self.max_health/4;
+                    self.blood += 5;
+                    format!("You fall into a trap and you bleed out for 5 moves, -{}HP", self.max_health/4)
+                } else {
+                    String::from("Trap doesn't work so you don't fall into a trap")
+                }
+            }
+            TrapType::Dart => {
+                if rng.range(1, 101) <= 70-self.agility*5 {
+                    self.health -= 2;
+                    self.poison = self.poison.max(8);
+                    String::from("A poison dart shoots out of the wall and hits you, -2HP")
+                } else {
+                    String::from("A dart shoots out of the wall and whistles past you")
+                }
+            }
+            TrapType::Alarm => {
+                map.alarm = true;
+                String::from("You trip a wire and a bell rings out, the whole floor is awake!")
+            }
+            TrapType::Pit => {
+                let damage = self.max_health/10;
+                self.health -= damage;
+                self.descend = true;
+                format!("The floor gives way and you fall to the floor below, -{}HP", damage)
+            }
+            TrapType::Teleport => match map.random_reachable(idx) {
+                Some(dest) => {
+                    self.pos = (dest as i32 % map.width, dest as i32 / map.width);
+                    self.update_fov(map);
+                    String::from("A glyph flashes under your feet and the world twists around you")
+                }
+                None => String::from("A glyph flashes under your feet, but nothing happens"),
+            },
+            TrapType::Gas => {
+                map.release_gas((idx as i32 % map.width, idx as i32 / map.width));
+                map.source[idx] = TileType::Floor;
+                String::from("A cloud of poison gas bursts out of the floor!")
+            }
+            TrapType::Ceiling => {
+                let damage = self.max_health/5;
+                map.source[idx] = TileType::Rubble;
+                self.health -= damage;
+                format!("The ceiling collapses on you, -{}HP", damage)
+            }
+        }
+    }
+
+    pub fn disarm(&mut self, map: &mut WorldLocation) -> String {
+        let mut rng = RandomNumberGenerator::new();
+        for y in self.pos.1-1..=self.pos.1+1 {
+            for x in self.pos.0-1..=self.pos.0+1 {
+                let idx = xy_idx(x, y, map.width);
+                let (trap, chance) = match map.source[idx] {
+                    TileType::TrapFound(trap) => (trap, trap.disarm() + self.agility*10 - (self.weight/2.0) as i32),
+                    TileType::TrapSet(trap) => (trap, 100),
+                    _ => continue,
+                };
+
+                let roll = rng.range(1, 101);
+                return if roll <= chance {
+                    map.source[idx] = TileType::Floor;
+                    let kit = ItemType::TrapKit(trap);
+                    if trap.is_portable() && self.weight + Player::weight_of(&kit) <= self.max_weight
+                        && self.inventory.len() < 25 {
+                        self.weight += Player::weight_of(&kit);
+                        self.inventory.push(kit);
+                        format!("You disarm the {} and take it with you", trap.name())
+                    } else {
+                        format!("You disarm the {}", trap.name())
+                    }
+                } else if roll > chance + 30 {
+                    format!("You fumble with the {} and set it off!\n{}", trap.name(), self.spring_trap(trap, idx, map))
+                } else {
+                    format!("You fail to disarm the {}", trap.name())
+                };
+            }
+        }
+        String::from("There is no trap nearby to disarm")
+    }
+
+    pub fn place_trap(&mut self, key: VirtualKeyCode, map: &mut WorldLocation) -> String {
+        let trap = match self.placing {
+            Some(trap) => trap,
+            None => return String::new(),
+        };
+
+        let (dx, dy) = match key {
+            VirtualKeyCode::Left | VirtualKeyCode::A => (-1, 0),
+            VirtualKeyCode::Right | VirtualKeyCode::D => (1, 0),
+            VirtualKeyCode::Up | VirtualKeyCode::W => (0, -1),
+            VirtualKeyCode::Down | VirtualKeyCode::S => (0, 1),
+            VirtualKeyCode::Back => {
+                self.placing = None;
+                self.weight += Player::weight_of(&ItemType::TrapKit(trap));
+                self.inventory.push(ItemType::TrapKit(trap));
+                return format!("You put the {} kit away", trap.name())
+            }
+            _ => return String::new(),
+        };
+
+        let (x, y) = (self.pos.0 + dx, self.pos.1 + dy);
+        let idx = xy_idx(x, y, map.width);
+        if map.is_solid((x, y)) || !matches!(map.source[idx], TileType::Floor | TileType::BloodStain | TileType::Ash) {
+            return String::from("You can't set a trap there")
+        }
+
+        map.source[idx] = TileType::TrapSet(trap);
+        map.colors[idx] = trap.color();
+        self.placing = None;
+        format!("You set the {}", trap.name())
+    }
+}